@@ -1,3 +1,5 @@
 fn main() {
+    // The headless `kiro` CLI builds without Tauri
+    #[cfg(feature = "gui")]
     tauri_build::build()
 }
@@ -0,0 +1,440 @@
+//! `kiro` command-line interface to the notes database used by the GUI
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
+use chrono::{Local, NaiveDate};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use zeroize::Zeroizing;
+
+use kiro_gui_lib::config::Config;
+use kiro_gui_lib::error::{KiroError, Result};
+use kiro_gui_lib::store::{ExportFormat, ExportOptions, Note, NoteStore, NoteTemplate, TitleStrategy};
+
+#[derive(Parser)]
+#[command(name = "kiro", version, about = "Search and edit Kiro notes from the terminal")]
+struct Cli {
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Notes database to use instead of the current vault's; also read
+    /// from KIRO_DB
+    #[arg(long, global = true, value_name = "PATH")]
+    db: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Cmd,
+}
+
+#[derive(Subcommand)]
+enum Cmd {
+    /// Search notes; supports the same y:/m:/journal: filters as the app
+    Search {
+        query: Vec<String>,
+        #[arg(short = 'n', long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// Print a note
+    Show { id: i64 },
+    /// Create a note. The body comes from --body, stdin or $EDITOR.
+    New {
+        title: String,
+        #[arg(short, long)]
+        body: Option<String>,
+        /// Start from a note template; the title fills its `title` variable
+        #[arg(short, long, conflicts_with = "body")]
+        template: Option<String>,
+        /// Answer one of the template's prompts
+        #[arg(long = "var", value_name = "NAME=VALUE", requires = "template")]
+        vars: Vec<String>,
+    },
+    /// List note templates and the variables they ask for
+    Templates,
+    /// Open the daily note for a date, creating it if needed, and print its
+    /// id. Edits it in $EDITOR when run in a terminal.
+    Journal {
+        /// Date as YYYY-MM-DD, today if not given
+        date: Option<NaiveDate>,
+    },
+    /// Edit a note's body in $EDITOR
+    Edit { id: i64 },
+    /// Delete notes
+    Rm {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// Import text files or Kiro exports
+    Import {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Title::FileStem)]
+        title: Title,
+    },
+    /// Export notes, all of them unless ids are given
+    Export {
+        ids: Vec<i64>,
+        #[arg(short, long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+        /// Directory to export into
+        #[arg(short, long, default_value = ".")]
+        dir: PathBuf,
+        /// Write a single zip archive
+        #[arg(long)]
+        archive: bool,
+        /// Export template name from ~/.config/kiro/export-templates
+        #[arg(long)]
+        template: Option<String>,
+    },
+    /// Show database statistics
+    Stats,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Title {
+    FileStem,
+    FirstLine,
+    FirstHeading,
+    FrontMatter,
+}
+
+impl From<Title> for TitleStrategy {
+    fn from(title: Title) -> Self {
+        match title {
+            Title::FileStem => TitleStrategy::FileStem,
+            Title::FirstLine => TitleStrategy::FirstLine,
+            Title::FirstHeading => TitleStrategy::FirstHeading,
+            Title::FrontMatter => TitleStrategy::FrontMatter,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Markdown,
+    Text,
+    Html,
+    Json,
+    Site,
+}
+
+impl From<Format> for ExportFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Markdown => ExportFormat::Markdown,
+            Format::Text => ExportFormat::PlainText,
+            Format::Html => ExportFormat::Html,
+            Format::Json => ExportFormat::Json,
+            Format::Site => ExportFormat::Site,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Stats {
+    path: PathBuf,
+    size_bytes: u64,
+    schema_version: i32,
+    encrypted: bool,
+    notes: usize,
+    secret_notes: usize,
+    words: usize,
+    oldest: Option<String>,
+    newest: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("kiro: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let (config, store) = open_store(cli.db)?;
+    let json = cli.json;
+
+    match cli.command {
+        Cmd::Search { query, limit } => {
+            let results = store.search(&query.join(" "), limit)?;
+            if json {
+                return print_json(&results);
+            }
+            for result in results {
+                println!(
+                    "{:>6}  {}  {:>6}  {}",
+                    result.id,
+                    result.date_str(),
+                    result.words_str(),
+                    result.display_text(80)
+                );
+            }
+        }
+        Cmd::Show { id } => {
+            let mut note = store.get(id)?.ok_or(KiroError::NoteNotFound(id))?;
+            if note.locked {
+                note = store.unlock_note(id, &read_passphrase(&format!("Passphrase for \"{}\": ", note.title))?)?;
+            }
+            if json {
+                return print_json(&note);
+            }
+            println!("# {}", note.title);
+            println!(
+                "Created {}  Updated {}\n",
+                note.created_at.format("%Y-%m-%d %H:%M"),
+                note.updated_at.format("%Y-%m-%d %H:%M")
+            );
+            println!("{}", note.body);
+        }
+        Cmd::New {
+            title,
+            template: Some(template),
+            vars,
+            ..
+        } => {
+            let mut values = HashMap::from([("title".to_string(), title)]);
+            for var in vars {
+                let (name, value) = var
+                    .split_once('=')
+                    .ok_or_else(|| KiroError::validation("var", format!("'{}' is not NAME=VALUE", var)))?;
+                values.insert(name.to_string(), value.to_string());
+            }
+            let note = NoteTemplate::load(&template)?.render(&values, Local::now().naive_local())?;
+            let body = if std::io::stdin().is_terminal() {
+                edit_text("new", &note.body)?
+            } else {
+                note.body
+            };
+            let id = store.create(&note.title, &body)?;
+            print_id(json, "created", id)?;
+        }
+        Cmd::New { title, body, .. } => {
+            let body = match body {
+                Some(body) => body,
+                None if !std::io::stdin().is_terminal() => {
+                    let mut body = String::new();
+                    std::io::stdin().read_to_string(&mut body)?;
+                    body
+                }
+                None => edit_text("new", "")?,
+            };
+            let id = store.create(&title, &body)?;
+            print_id(json, "created", id)?;
+        }
+        Cmd::Templates => {
+            let templates = NoteTemplate::list();
+            if json {
+                return print_json(&templates);
+            }
+            for template in templates {
+                println!("{:<20}  {}", template.name, template.prompts.join(", "));
+            }
+        }
+        Cmd::Journal { date } => {
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            let mut note = store.open_daily_note(date, &config.journal)?;
+            if std::io::stdin().is_terminal() {
+                if note.locked {
+                    note = store.unlock_note(note.id, &read_passphrase(&format!("Passphrase for \"{}\": ", note.title))?)?;
+                }
+                let body = edit_text(&date.to_string(), &note.body)?;
+                if body != note.body {
+                    store.update(note.id, &body)?;
+                }
+            }
+            print_id(json, "journal", note.id)?;
+        }
+        Cmd::Edit { id } => {
+            let mut note = store.get(id)?.ok_or(KiroError::NoteNotFound(id))?;
+            if note.locked {
+                note = store.unlock_note(id, &read_passphrase(&format!("Passphrase for \"{}\": ", note.title))?)?;
+            }
+            let body = edit_text(&id.to_string(), &note.body)?;
+            if body != note.body {
+                store.update(id, &body)?;
+            }
+            print_id(json, "updated", id)?;
+        }
+        Cmd::Rm { ids } => {
+            let deleted = store.delete_many(&ids)?;
+            if json {
+                return print_json(&serde_json::json!({ "deleted": deleted }));
+            }
+            println!("Deleted {} notes", deleted);
+        }
+        Cmd::Import { paths, title } => {
+            let (imported, skipped, ids) = store.import_files(&paths, &title.into())?;
+            if json {
+                return print_json(&serde_json::json!({
+                    "imported": imported,
+                    "skipped": skipped,
+                    "ids": ids,
+                }));
+            }
+            println!("Imported {} notes, skipped {}", imported, skipped);
+        }
+        Cmd::Export { ids, format, dir, archive, template } => {
+            let ids: HashSet<i64> = if ids.is_empty() {
+                store.ids()?.into_iter().collect()
+            } else {
+                ids.into_iter().collect()
+            };
+            let options = ExportOptions {
+                format: format.into(),
+                archive,
+                template,
+                ..Default::default()
+            };
+            let (count, path) = store.export_notes(&ids, &dir, &options)?;
+            if json {
+                return print_json(&serde_json::json!({ "exported": count, "path": path }));
+            }
+            println!("Exported {} notes to {}", count, path.display());
+        }
+        Cmd::Stats => {
+            let stats = stats(&store)?;
+            if json {
+                return print_json(&stats);
+            }
+            println!("Database:  {} ({} KiB)", stats.path.display(), stats.size_bytes / 1024);
+            println!("Schema:    v{}{}", stats.schema_version, if stats.encrypted { ", encrypted" } else { "" });
+            println!("Notes:     {} ({} secret)", stats.notes, stats.secret_notes);
+            println!("Words:     {}", stats.words);
+            if let (Some(oldest), Some(newest)) = (&stats.oldest, &stats.newest) {
+                println!("Created:   {} to {}", oldest, newest);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Open the GUI's current vault, or `db`, asking for the passphrase if it
+/// is encrypted. `KIRO_PASSPHRASE` supplies it for scripts.
+fn open_store(db: Option<PathBuf>) -> Result<(Config, NoteStore)> {
+    let (config, diagnostics) = Config::load();
+    for diagnostic in diagnostics {
+        eprintln!("kiro: config.json: {}", diagnostic.message);
+    }
+    let path = kiro_gui_lib::db_path(db, &config);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let store = NoteStore::open(&path, &config.backup)?;
+    if store.is_locked() {
+        let passphrase = match std::env::var("KIRO_PASSPHRASE") {
+            Ok(passphrase) => Zeroizing::new(passphrase),
+            Err(_) => read_passphrase("Passphrase: ")?,
+        };
+        store.unlock_notes(&passphrase)?;
+    }
+    Ok((config, store))
+}
+
+fn read_passphrase(prompt: &str) -> Result<Zeroizing<String>> {
+    if !std::io::stdin().is_terminal() {
+        return Err(KiroError::Locked);
+    }
+    Ok(Zeroizing::new(rpassword::prompt_password(prompt)?))
+}
+
+/// Let the user edit `text` in $VISUAL, $EDITOR or vi and return the result
+fn edit_text(name: &str, text: &str) -> Result<String> {
+    let (path, mut file) = create_temp_file(name)?;
+    let written = file.write_all(text.as_bytes());
+    drop(file);
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&path);
+        return Err(e.into());
+    }
+
+    let result = run_editor(&path).and_then(|()| Ok(std::fs::read_to_string(&path)?));
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+/// Create a new file in the temp directory that only the current user can
+/// read, since it may hold an unlocked secret note. Never opens an
+/// existing file or follows a symlink planted under the same name.
+fn create_temp_file(name: &str) -> Result<(PathBuf, File)> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let stamp = Local::now().timestamp_nanos_opt().unwrap_or_default();
+    let mut attempt = 0;
+    loop {
+        let path = std::env::temp_dir().join(format!(
+            "kiro-{}-{}-{:x}-{}.md",
+            name,
+            std::process::id(),
+            stamp,
+            attempt
+        ));
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn run_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    // $EDITOR may carry arguments, e.g. "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program).args(parts).arg(path).status()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("{} exited with {}", editor, status)).into());
+    }
+    Ok(())
+}
+
+fn stats(store: &NoteStore) -> Result<Stats> {
+    let ids = store.ids()?;
+    let notes: Vec<Note> = store.get_many(&ids)?;
+
+    let oldest = notes.iter().map(|n| n.created_at).min();
+    let newest = notes.iter().map(|n| n.created_at).max();
+
+    Ok(Stats {
+        path: store.path().to_path_buf(),
+        size_bytes: std::fs::metadata(store.path()).map(|m| m.len()).unwrap_or(0),
+        schema_version: store.schema_version()?,
+        encrypted: store.is_encrypted(),
+        notes: notes.len(),
+        secret_notes: notes.iter().filter(|n| n.encrypted).count(),
+        words: notes.iter().map(|n| n.body.split_whitespace().count()).sum(),
+        oldest: oldest.map(|d| d.format("%Y-%m-%d").to_string()),
+        newest: newest.map(|d| d.format("%Y-%m-%d").to_string()),
+    })
+}
+
+fn print_id(json: bool, action: &str, id: i64) -> Result<()> {
+    if json {
+        return print_json(&serde_json::json!({ action: id }));
+    }
+    println!("{}", id);
+    Ok(())
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    let text = serde_json::to_string_pretty(value)
+        .map_err(|e| KiroError::ExportFailed(e.to_string()))?;
+    println!("{}", text);
+    Ok(())
+}
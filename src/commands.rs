@@ -1,16 +1,46 @@
 use std::collections::HashSet;
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
 
+use chrono::{Local, NaiveDate};
 use serde::{Deserialize, Serialize};
-use tauri::{State, AppHandle, Manager};
-
-use crate::config::Config;
-use crate::store::{Note, NoteStore, SearchResult};
+use tauri::{State, AppHandle, Emitter, Manager};
+use zeroize::Zeroizing;
+
+use crate::config::keybindings;
+use crate::config::{
+    ApiSettings, BackupSettings, Config, ConfigDiagnostic, JournalSettings, Keymap, ScanSettings, SecuritySettings,
+    Vault, DEFAULT_VAULT,
+};
+use crate::error::{KiroError, Result};
+use crate::scan::glob::GlobSet;
+use crate::scan::{ScanOptions, ScanResult, Scanner};
+use crate::store::backup;
+use crate::store::{
+    ExportOptions, ExportTemplate, Note, NoteStore, NoteTemplate, NoteTemplateInfo, SearchResult, TitleStrategy,
+};
 
 pub struct AppState {
     pub store: Mutex<NoteStore>,
     pub config: Mutex<Config>,
+    /// Problems found in config.json when it was loaded
+    pub config_diagnostics: Mutex<Vec<ConfigDiagnostic>>,
+    /// Last time a command read or wrote notes, for auto-lock
+    pub last_activity: Mutex<Instant>,
+    /// Whether the desktop prefers dark colors, once the window reports it
+    pub system_dark: Mutex<Option<bool>>,
+    /// Preset last sent with `theme-changed`
+    pub shown_theme: Mutex<String>,
+}
+
+/// Lock the store for a command that touches note contents, failing if
+/// encrypted notes are locked
+fn unlocked_store(state: &AppState) -> Result<MutexGuard<'_, NoteStore>> {
+    let store = state.store.lock()?;
+    store.ensure_unlocked()?;
+    *state.last_activity.lock()? = Instant::now();
+    Ok(store)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,10 +51,9 @@ pub struct DirEntry {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct FileEntry {
-    pub path: PathBuf,
-    pub name: String,
-    pub size: u64,
+pub struct LockState {
+    pub encrypted: bool,
+    pub locked: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -34,59 +63,164 @@ pub struct ImportResult {
     pub ids: Vec<i64>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultInfo {
+    pub name: String,
+    pub path: PathBuf,
+    /// Whether this is the database the app has open
+    pub current: bool,
+    pub exists: bool,
+}
+
+impl VaultInfo {
+    fn new(vault: Vault, current: bool) -> Self {
+        Self {
+            current,
+            exists: vault.path.exists(),
+            name: vault.name,
+            path: vault.path,
+        }
+    }
+}
+
 // Search & Notes
 #[tauri::command]
-pub fn search(state: State<AppState>, query: &str, limit: usize) -> Result<Vec<SearchResult>, String> {
-    let store = state.store.lock().map_err(|e| e.to_string())?;
-    store.search(query, limit).map_err(|e| e.to_string())
+pub fn search(state: State<AppState>, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+    let store = unlocked_store(&state)?;
+    store.search(query, limit)
+}
+
+#[tauri::command]
+pub fn get_note(state: State<AppState>, id: i64) -> Result<Option<Note>> {
+    let store = unlocked_store(&state)?;
+    store.get(id)
+}
+
+#[tauri::command]
+pub fn create_note(state: State<AppState>, title: &str, body: &str) -> Result<i64> {
+    let store = unlocked_store(&state)?;
+    store.create(title, body)
+}
+
+#[tauri::command]
+pub fn update_note(state: State<AppState>, id: i64, body: &str) -> Result<()> {
+    let store = unlocked_store(&state)?;
+    store.update(id, body)
+}
+
+#[tauri::command]
+pub fn update_note_full(state: State<AppState>, id: i64, title: &str, body: &str) -> Result<()> {
+    let store = unlocked_store(&state)?;
+    store.update_title_and_body(id, title, body)
+}
+
+#[tauri::command]
+pub fn delete_notes(state: State<AppState>, ids: Vec<i64>) -> Result<usize> {
+    let store = unlocked_store(&state)?;
+    store.delete_many(&ids)
+}
+
+#[tauri::command]
+pub fn get_note_count(state: State<AppState>) -> Result<usize> {
+    let store = state.store.lock()?;
+    store.count()
+}
+
+#[tauri::command]
+pub fn seed_notes(state: State<AppState>, count: usize) -> Result<()> {
+    let store = unlocked_store(&state)?;
+    store.seed(count)
+}
+
+// Note templates
+/// A note made from a template, and where the template put the cursor
+/// A loaded keymap profile and what was skipped from it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeymapProfile {
+    pub keymap: Keymap,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateNote {
+    pub id: i64,
+    pub cursor: Option<usize>,
 }
 
 #[tauri::command]
-pub fn get_note(state: State<AppState>, id: i64) -> Result<Option<Note>, String> {
-    let store = state.store.lock().map_err(|e| e.to_string())?;
-    store.get(id).map_err(|e| e.to_string())
+pub fn list_note_templates() -> Vec<NoteTemplateInfo> {
+    NoteTemplate::list()
 }
 
 #[tauri::command]
-pub fn create_note(state: State<AppState>, title: &str, body: &str) -> Result<i64, String> {
-    let store = state.store.lock().map_err(|e| e.to_string())?;
-    store.create(title, body).map_err(|e| e.to_string())
+pub fn get_note_template(name: &str) -> Result<String> {
+    NoteTemplate::source(name)
 }
 
 #[tauri::command]
-pub fn update_note(state: State<AppState>, id: i64, body: &str) -> Result<(), String> {
-    let store = state.store.lock().map_err(|e| e.to_string())?;
-    store.update(id, body).map_err(|e| e.to_string())
+pub fn save_note_template(name: &str, source: &str) -> Result<()> {
+    NoteTemplate::save(name, source)
 }
 
 #[tauri::command]
-pub fn update_note_full(state: State<AppState>, id: i64, title: &str, body: &str) -> Result<(), String> {
-    let store = state.store.lock().map_err(|e| e.to_string())?;
-    store.update_title_and_body(id, title, body).map_err(|e| e.to_string())
+pub fn delete_note_template(name: &str) -> Result<()> {
+    NoteTemplate::delete(name)
 }
 
+/// Create a note from a template, filling its prompts from `vars`
 #[tauri::command]
-pub fn delete_notes(state: State<AppState>, ids: Vec<i64>) -> Result<usize, String> {
-    let store = state.store.lock().map_err(|e| e.to_string())?;
-    store.delete_many(&ids).map_err(|e| e.to_string())
+pub fn create_note_from_template(
+    state: State<AppState>,
+    template: &str,
+    vars: HashMap<String, String>,
+) -> Result<TemplateNote> {
+    let note = NoteTemplate::load(template)?.render(&vars, Local::now().naive_local())?;
+    let store = unlocked_store(&state)?;
+    let id = store.create(&note.title, &note.body)?;
+    Ok(TemplateNote { id, cursor: note.cursor })
 }
 
+// Daily notes
+/// The daily note for `date`, today if not given, created if there is none
 #[tauri::command]
-pub fn get_note_count(state: State<AppState>) -> Result<usize, String> {
-    let store = state.store.lock().map_err(|e| e.to_string())?;
-    store.count().map_err(|e| e.to_string())
+pub fn open_daily_note(state: State<AppState>, date: Option<NaiveDate>) -> Result<Note> {
+    let settings = state.config.lock()?.journal.clone();
+    let date = date.unwrap_or_else(|| Local::now().date_naive());
+    let store = unlocked_store(&state)?;
+    store.open_daily_note(date, &settings)
 }
 
+/// The nearest existing daily note before `date`, or after it if `forward`
 #[tauri::command]
-pub fn seed_notes(state: State<AppState>, count: usize) -> Result<(), String> {
-    let store = state.store.lock().map_err(|e| e.to_string())?;
-    store.seed(count).map_err(|e| e.to_string())
+pub fn adjacent_daily_note(state: State<AppState>, date: NaiveDate, forward: bool) -> Result<Option<Note>> {
+    let store = unlocked_store(&state)?;
+    store.adjacent_daily_note(date, forward)
+}
+
+#[tauri::command]
+pub fn get_journal_settings(state: State<AppState>) -> Result<JournalSettings> {
+    let config = state.config.lock()?;
+    Ok(config.journal.clone())
+}
+
+#[tauri::command]
+pub fn save_journal_settings(state: State<AppState>, mut settings: JournalSettings) -> Result<()> {
+    JournalSettings::validate_title_format(&settings.title_format)?;
+    settings.template = settings.template.filter(|name| !name.is_empty());
+    if let Some(name) = &settings.template {
+        NoteTemplate::load(name)?;
+    }
+
+    let mut config = state.config.lock()?;
+    settings.extra = std::mem::take(&mut config.journal.extra);
+    config.journal = settings;
+    config.save()
 }
 
 // Import/Export
 #[tauri::command]
-pub fn get_home_directories() -> Result<Vec<DirEntry>, String> {
-    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+pub fn get_home_directories() -> Result<Vec<DirEntry>> {
+    let home = dirs::home_dir().ok_or_else(|| KiroError::Unavailable("Home directory".into()))?;
 
     let mut entries = Vec::new();
     if let Ok(read_dir) = std::fs::read_dir(&home) {
@@ -115,187 +249,652 @@ pub fn get_home_directories() -> Result<Vec<DirEntry>, String> {
 }
 
 #[tauri::command]
-pub fn scan_directories(dirs: Vec<PathBuf>, pattern: &str) -> Result<Vec<FileEntry>, String> {
-    let regex = regex::Regex::new(&glob_to_regex(pattern))
-        .map_err(|e| format!("Invalid pattern: {}", e))?;
+pub fn scan_directories(
+    state: State<AppState>,
+    dirs: Vec<PathBuf>,
+    patterns: Vec<String>,
+    options: Option<ScanOptions>,
+    title_strategy: Option<TitleStrategy>,
+) -> Result<ScanResult> {
+    let globs = GlobSet::new(&patterns)?;
+    let options = options.unwrap_or_default();
+    let strategy = title_strategy.unwrap_or_default();
+
+    let settings = {
+        let config = state.config.lock()?;
+        config.scan.clone()
+    };
+
+    let mut result = Scanner::new(&settings, &globs, &options).scan(&dirs);
+
+    if options.check_existing {
+        let store = unlocked_store(&state)?;
+        for file in &mut result.files {
+            // Unreadable files are left as None rather than failing the scan
+            file.exists_in_store = store.file_exists(&file.path, &strategy).ok();
+        }
+    }
 
-    let mut files = Vec::new();
+    Ok(result)
+}
 
-    for dir in dirs {
-        scan_dir_recursive(&dir, &regex, &mut files);
-    }
+#[tauri::command]
+pub fn import_files(
+    state: State<AppState>,
+    paths: Vec<PathBuf>,
+    title_strategy: Option<TitleStrategy>,
+) -> Result<ImportResult> {
+    let store = unlocked_store(&state)?;
+    let strategy = title_strategy.unwrap_or_default();
+    let (imported, skipped, ids) = store.import_files(&paths, &strategy)?;
+    Ok(ImportResult { imported, skipped, ids })
+}
+
+#[tauri::command]
+pub fn export_notes(state: State<AppState>, ids: Vec<i64>, options: ExportOptions) -> Result<String> {
+    let store = unlocked_store(&state)?;
+
+    let export_dir = match &options.destination {
+        // The app's working directory means nothing to the user
+        Some(dir) if dir.is_relative() => {
+            return Err(KiroError::validation("destination", "enter a full path to the export folder"))
+        }
+        Some(dir) => dir.clone(),
+        None => {
+            let download_dir = dirs::download_dir()
+                .or_else(|| dirs::home_dir().map(|p| p.join("Downloads")))
+                .ok_or_else(|| KiroError::Unavailable("Downloads directory".into()))?;
+            download_dir.join("kiro-export")
+        }
+    };
+    let id_set: HashSet<i64> = ids.into_iter().collect();
+
+    let (count, path) = store.export_notes(&id_set, &export_dir, &options)?;
 
-    files.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    Ok(files)
+    Ok(format!("Exported {} notes to {:?}", count, path))
 }
 
-fn scan_dir_recursive(dir: &PathBuf, regex: &regex::Regex, files: &mut Vec<FileEntry>) {
-    if let Ok(read_dir) = std::fs::read_dir(dir) {
-        for entry in read_dir.filter_map(|e| e.ok()) {
-            let path = entry.path();
+#[tauri::command]
+pub fn list_export_templates() -> Vec<String> {
+    ExportTemplate::list()
+}
 
-            if path.is_dir() {
-                // Skip hidden directories
-                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                if !name.starts_with('.') {
-                    scan_dir_recursive(&path, regex, files);
-                }
-            } else if path.is_file() {
-                let name = path.file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("")
-                    .to_string();
+// Config
+#[tauri::command]
+pub fn get_config(state: State<AppState>) -> Result<Config> {
+    let config = state.config.lock()?;
+    Ok(config.clone())
+}
 
-                if regex.is_match(&name) {
-                    let size = std::fs::metadata(&path)
-                        .map(|m| m.len())
-                        .unwrap_or(0);
+#[tauri::command]
+pub fn get_scan_directories(state: State<AppState>) -> Result<Vec<PathBuf>> {
+    let config = state.config.lock()?;
+    Ok(config.scan_directories.iter().cloned().collect())
+}
 
-                    files.push(FileEntry {
-                        path: path.clone(),
-                        name,
-                        size,
-                    });
-                }
-            }
-        }
-    }
+#[tauri::command]
+pub fn set_scan_directories(state: State<AppState>, dirs: Vec<PathBuf>) -> Result<()> {
+    let mut config = state.config.lock()?;
+    let dir_set: HashSet<PathBuf> = dirs.into_iter().collect();
+    config.set_scan_directories(dir_set)
 }
 
-fn glob_to_regex(pattern: &str) -> String {
-    let mut regex = String::from("^");
-    for c in pattern.chars() {
-        match c {
-            '*' => regex.push_str(".*"),
-            '?' => regex.push('.'),
-            '.' => regex.push_str("\\."),
-            c => regex.push(c),
-        }
+#[tauri::command]
+pub fn get_scan_settings(state: State<AppState>) -> Result<ScanSettings> {
+    let config = state.config.lock()?;
+    Ok(config.scan.clone())
+}
+
+#[tauri::command]
+pub fn save_scan_settings(state: State<AppState>, mut settings: ScanSettings) -> Result<()> {
+    let mut config = state.config.lock()?;
+    // The frontend never sees keys from newer versions; keep them
+    settings.extra = std::mem::take(&mut config.scan.extra);
+    config.scan = settings;
+    config.save()
+}
+
+// Backup & Restore
+#[tauri::command]
+pub fn backup_database(state: State<AppState>, path: PathBuf) -> Result<String> {
+    let store = state.store.lock()?;
+    store.backup_to(&path)?;
+    Ok(format!("Backed up database to {:?}", path))
+}
+
+#[tauri::command]
+pub fn restore_database(state: State<AppState>, path: PathBuf) -> Result<String> {
+    let settings = state.config.lock()?.backup.clone();
+    let mut store = state.store.lock()?;
+
+    // Check the backup before touching the current database
+    backup::validate_backup(&path)?;
+
+    // Keep a copy of the current notes in case the restore was a mistake
+    let dir = settings.dir_for(store.path());
+    let safety = store.auto_backup(&dir, settings.keep)?;
+
+    store.restore_from(&path)?;
+    Ok(format!(
+        "Restored database from {:?} (previous notes saved to {:?})",
+        path, safety
+    ))
+}
+
+#[tauri::command]
+pub fn list_backups(state: State<AppState>) -> Result<Vec<PathBuf>> {
+    let settings = state.config.lock()?.backup.clone();
+    let store = state.store.lock()?;
+    Ok(backup::list_backups(&settings.dir_for(store.path())))
+}
+
+#[tauri::command]
+pub fn get_backup_settings(state: State<AppState>) -> Result<BackupSettings> {
+    let config = state.config.lock()?;
+    Ok(config.backup.clone())
+}
+
+#[tauri::command]
+pub fn save_backup_settings(state: State<AppState>, mut settings: BackupSettings) -> Result<()> {
+    let mut config = state.config.lock()?;
+    settings.extra = std::mem::take(&mut config.backup.extra);
+    state.store.lock()?.set_backup_settings(&settings);
+    config.backup = settings;
+    config.save()
+}
+
+// Encryption
+#[tauri::command]
+pub fn get_lock_state(state: State<AppState>) -> Result<LockState> {
+    let store = state.store.lock()?;
+    Ok(LockState {
+        encrypted: store.is_encrypted(),
+        locked: store.is_locked(),
+    })
+}
+
+#[tauri::command]
+pub fn unlock_notes(state: State<AppState>, passphrase: String) -> Result<()> {
+    let passphrase = Zeroizing::new(passphrase);
+    let store = state.store.lock()?;
+    store.unlock_notes(&passphrase)?;
+    *state.last_activity.lock()? = Instant::now();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn lock_notes(state: State<AppState>) -> Result<()> {
+    let store = state.store.lock()?;
+    store.lock_notes();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_note_secret(state: State<AppState>, id: i64, passphrase: String) -> Result<()> {
+    let passphrase = Zeroizing::new(passphrase);
+    let store = unlocked_store(&state)?;
+    store.set_note_secret(id, &passphrase)
+}
+
+#[tauri::command]
+pub fn clear_note_secret(state: State<AppState>, id: i64, passphrase: String) -> Result<()> {
+    let passphrase = Zeroizing::new(passphrase);
+    let store = unlocked_store(&state)?;
+    store.clear_note_secret(id, &passphrase)
+}
+
+#[tauri::command]
+pub fn unlock_note(state: State<AppState>, id: i64, passphrase: String) -> Result<Note> {
+    let passphrase = Zeroizing::new(passphrase);
+    let store = unlocked_store(&state)?;
+    store.unlock_note(id, &passphrase)
+}
+
+#[tauri::command]
+pub fn lock_note(state: State<AppState>, id: i64) -> Result<()> {
+    let store = state.store.lock()?;
+    store.lock_note(id);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn enable_encryption(state: State<AppState>, passphrase: String) -> Result<String> {
+    let passphrase = Zeroizing::new(passphrase);
+    let settings = state.config.lock()?.backup.clone();
+    let store = state.store.lock()?;
+
+    // Keep a plaintext copy only until the encrypted notes are checked
+    let dir = settings.dir_for(store.path());
+    store.auto_backup(&dir, settings.keep)?;
+    store.enable_encryption(&passphrase)?;
+
+    // Older backups would leave the notes readable next to the database.
+    // The count leaves out the safety copy taken above.
+    let removed = backup::remove_unencrypted_backups(&dir)?.saturating_sub(1);
+    store.auto_backup(&dir, settings.keep)?;
+    Ok(format!(
+        "Notes encrypted. Deleted {} older unencrypted backup(s); copies of the database made elsewhere are not encrypted",
+        removed
+    ))
+}
+
+#[tauri::command]
+pub fn disable_encryption(state: State<AppState>, passphrase: String) -> Result<()> {
+    let passphrase = Zeroizing::new(passphrase);
+    let store = state.store.lock()?;
+    store.disable_encryption(&passphrase)
+}
+
+#[tauri::command]
+pub fn get_security_settings(state: State<AppState>) -> Result<SecuritySettings> {
+    let config = state.config.lock()?;
+    Ok(config.security.clone())
+}
+
+#[tauri::command]
+pub fn save_security_settings(state: State<AppState>, mut settings: SecuritySettings) -> Result<()> {
+    let mut config = state.config.lock()?;
+    settings.extra = std::mem::take(&mut config.security.extra);
+    config.security = settings;
+    config.save()
+}
+
+/// The HTTP API server reads these at startup, so changes apply on restart
+#[tauri::command]
+pub fn get_api_settings(state: State<AppState>) -> Result<ApiSettings> {
+    let config = state.config.lock()?;
+    Ok(config.api.clone())
+}
+
+#[tauri::command]
+pub fn save_api_settings(state: State<AppState>, mut settings: ApiSettings) -> Result<()> {
+    let mut config = state.config.lock()?;
+    settings.extra = std::mem::take(&mut config.api.extra);
+    config.api = settings;
+    config.save()
+}
+
+/// Problems found in config.json at startup, each with the default used instead
+#[tauri::command]
+pub fn get_config_diagnostics(state: State<AppState>) -> Result<Vec<ConfigDiagnostic>> {
+    Ok(state.config_diagnostics.lock()?.clone())
+}
+
+// Vaults
+#[tauri::command]
+pub fn list_vaults(state: State<AppState>) -> Result<Vec<VaultInfo>> {
+    let open_path = state.store.lock()?.path().to_path_buf();
+    let config = state.config.lock()?;
+    Ok(config
+        .all_vaults()
+        .into_iter()
+        .map(|vault| {
+            let current = vault.path == open_path;
+            VaultInfo::new(vault, current)
+        })
+        .collect())
+}
+
+/// Add a vault with a new, empty database at `path`
+#[tauri::command]
+pub fn create_vault(state: State<AppState>, name: String, path: PathBuf) -> Result<VaultInfo> {
+    let vault = Vault::new(name.trim(), path);
+    let mut config = state.config.lock()?;
+    config.validate_new_vault(&vault)?;
+    if vault.path.exists() {
+        let message = format!("{} already exists; open it as a vault instead", vault.path.display());
+        return Err(KiroError::validation("path", message));
+    }
+
+    if let Some(parent) = vault.path.parent() {
+        std::fs::create_dir_all(parent)?;
     }
-    regex.push('$');
-    regex
+    NoteStore::open(&vault.path, &config.backup)?;
+
+    config.vaults.push(vault.clone());
+    config.save()?;
+    Ok(VaultInfo::new(vault, false))
 }
 
+/// Add a vault for an existing Kiro database
 #[tauri::command]
-pub fn import_files(state: State<AppState>, paths: Vec<PathBuf>) -> Result<ImportResult, String> {
-    let store = state.store.lock().map_err(|e| e.to_string())?;
-    let (imported, skipped, ids) = store.import_files(&paths).map_err(|e| e.to_string())?;
-    Ok(ImportResult { imported, skipped, ids })
+pub fn open_vault(state: State<AppState>, name: String, path: PathBuf) -> Result<VaultInfo> {
+    let vault = Vault::new(name.trim(), path);
+    let mut config = state.config.lock()?;
+    config.validate_new_vault(&vault)?;
+    backup::validate_backup(&vault.path).map_err(|e| match e {
+        KiroError::InvalidBackup(message) => KiroError::InvalidPath(message),
+        e => e,
+    })?;
+
+    config.vaults.push(vault.clone());
+    config.save()?;
+    Ok(VaultInfo::new(vault, false))
 }
 
+/// Make `name` the open database, and the one opened on the next start
 #[tauri::command]
-pub fn export_notes(state: State<AppState>, ids: Vec<i64>) -> Result<String, String> {
-    let store = state.store.lock().map_err(|e| e.to_string())?;
+pub fn switch_vault(app: AppHandle, state: State<AppState>, name: &str) -> Result<VaultInfo> {
+    let vault = state
+        .config
+        .lock()?
+        .find_vault(name)
+        .ok_or_else(|| KiroError::validation("name", format!("there is no vault named '{}'", name)))?;
+    switch_to(&app, &state, vault)
+}
 
-    let download_dir = dirs::download_dir()
-        .or_else(|| dirs::home_dir().map(|p| p.join("Downloads")))
-        .ok_or("Could not find downloads directory")?;
+/// Remove a vault from the list, leaving its database on disk. Closing the
+/// open vault switches to the default one.
+#[tauri::command]
+pub fn close_vault(app: AppHandle, state: State<AppState>, name: &str) -> Result<()> {
+    if name == DEFAULT_VAULT {
+        return Err(KiroError::validation("name", "the default vault can't be closed"));
+    }
+    let vault = state
+        .config
+        .lock()?
+        .vaults
+        .iter()
+        .find(|vault| vault.name == name)
+        .cloned()
+        .ok_or_else(|| KiroError::validation("name", format!("there is no vault named '{}'", name)))?;
+
+    let is_open = state.store.lock()?.path() == vault.path;
+    if is_open {
+        let default = Vault::new(DEFAULT_VAULT, crate::default_db_path());
+        switch_to(&app, &state, default)?;
+    }
 
-    let export_dir = download_dir.join("kiro-export");
-    let id_set: HashSet<i64> = ids.into_iter().collect();
+    let mut config = state.config.lock()?;
+    config.vaults.retain(|v| v.name != name);
+    if config.current_vault.as_deref() == Some(name) {
+        config.current_vault = None;
+    }
+    config.save()
+}
 
-    let count = store.export_notes(&id_set, &export_dir).map_err(|e| e.to_string())?;
+/// Swap the open `NoteStore` for `vault`'s database and tell the frontend
+/// with a `vault-changed` event. Only the default vault's database is
+/// created when missing; opening another would hide a moved or deleted
+/// database behind a new, empty one.
+fn switch_to(app: &AppHandle, state: &AppState, vault: Vault) -> Result<VaultInfo> {
+    if vault.name != DEFAULT_VAULT && !vault.path.exists() {
+        return Err(KiroError::validation("path", vault.missing_message()));
+    }
+    if let Some(parent) = vault.path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let backups = state.config.lock()?.backup.clone();
+    let store = NoteStore::open(&vault.path, &backups)?;
+    crate::gui::spawn_event_forwarder(app.clone(), store.subscribe());
+    // Dropping the old store closes its database and ends its forwarder
+    *state.store.lock()? = store;
+    *state.last_activity.lock()? = Instant::now();
+
+    {
+        let mut config = state.config.lock()?;
+        config.current_vault = (vault.name != DEFAULT_VAULT).then(|| vault.name.clone());
+        config.save()?;
+    }
 
-    Ok(format!("Exported {} notes to {:?}", count, export_dir))
+    let info = VaultInfo::new(vault, true);
+    let _ = app.emit("vault-changed", &info);
+    Ok(info)
 }
 
-// Config
+// Keybindings
+/// Chords for every action, defaults included
 #[tauri::command]
-pub fn get_config(state: State<AppState>) -> Result<Config, String> {
-    let config = state.config.lock().map_err(|e| e.to_string())?;
-    Ok(config.clone())
+pub fn get_keybindings(state: State<AppState>) -> Result<Keymap> {
+    let config = state.config.lock()?;
+    Ok(keybindings::resolve(&config.keybindings))
 }
 
+/// Replace the chords for `action`; an empty list leaves it unbound
 #[tauri::command]
-pub fn get_scan_directories(state: State<AppState>) -> Result<Vec<PathBuf>, String> {
-    let config = state.config.lock().map_err(|e| e.to_string())?;
-    Ok(config.scan_directories.iter().cloned().collect())
+pub fn set_keybinding(state: State<AppState>, action: String, chords: Vec<String>) -> Result<Keymap> {
+    let chords = keybindings::normalize_chords(&action, &chords)?;
+    let mut config = state.config.lock()?;
+    let mut overrides = config.keybindings.clone();
+    overrides.insert(action, chords);
+    keybindings::check_conflicts(&overrides)?;
+
+    config.keybindings = overrides;
+    config.save()?;
+    Ok(keybindings::resolve(&config.keybindings))
 }
 
+/// Restore the default chords for `action`, or for every action
 #[tauri::command]
-pub fn set_scan_directories(state: State<AppState>, dirs: Vec<PathBuf>) -> Result<(), String> {
-    let mut config = state.config.lock().map_err(|e| e.to_string())?;
-    let dir_set: HashSet<PathBuf> = dirs.into_iter().collect();
-    config.set_scan_directories(dir_set).map_err(|e| e.to_string())
+pub fn reset_keybindings(state: State<AppState>, action: Option<String>) -> Result<Keymap> {
+    let mut config = state.config.lock()?;
+    let mut overrides = config.keybindings.clone();
+    match &action {
+        Some(action) if !keybindings::is_action(action) => {
+            return Err(KiroError::validation(
+                format!("keybindings.{}", action),
+                format!("unknown action '{}'", action),
+            ));
+        }
+        Some(action) => {
+            overrides.remove(action);
+        }
+        None => overrides.retain(|action, _| !keybindings::is_action(action)),
+    }
+    keybindings::check_conflicts(&overrides)?;
+
+    config.keybindings = overrides;
+    config.save()?;
+    Ok(keybindings::resolve(&config.keybindings))
+}
+
+#[tauri::command]
+pub fn list_keymap_profiles() -> Vec<String> {
+    keybindings::list_profiles()
+}
+
+/// Save the current keymap as a profile in ~/.config/kiro/keymaps
+#[tauri::command]
+pub fn export_keymap_profile(state: State<AppState>, name: &str) -> Result<PathBuf> {
+    let keymap = keybindings::resolve(&state.config.lock()?.keybindings);
+    keybindings::save_profile(name, &keymap)
+}
+
+/// Replace the keymap with a saved profile. Actions the profile leaves out
+/// or that can't be used get their defaults.
+#[tauri::command]
+pub fn import_keymap_profile(state: State<AppState>, name: &str) -> Result<KeymapProfile> {
+    let (profile, warnings) = keybindings::load_profile(name)?;
+    let mut config = state.config.lock()?;
+    config.keybindings.retain(|action, _| !keybindings::is_action(action));
+    config.keybindings.extend(profile);
+    config.save()?;
+    Ok(KeymapProfile {
+        keymap: keybindings::resolve(&config.keybindings),
+        warnings,
+    })
 }
 
 // Theme settings
+use crate::config::themes::UserTheme;
 use crate::config::ThemeSettings;
 use std::collections::HashMap;
 
+/// Themes from ~/.config/kiro/themes; the built-in presets aren't included
+#[tauri::command]
+pub fn list_themes() -> Vec<UserTheme> {
+    UserTheme::list()
+}
+
+/// Copy a theme file into the themes directory
+#[tauri::command]
+pub fn import_theme(path: PathBuf) -> Result<UserTheme> {
+    UserTheme::import(&path)
+}
+
+#[tauri::command]
+pub fn export_theme(id: &str, path: PathBuf) -> Result<String> {
+    UserTheme::load(id)?.write(&path)?;
+    Ok(format!("Exported theme to {:?}", path))
+}
+
+/// Save the preset on screen and the custom colors as a new user theme
+/// named `name`, and switch to it
+#[tauri::command]
+pub fn create_theme(app: AppHandle, state: State<AppState>, name: &str) -> Result<UserTheme> {
+    let shown = active_theme(&state)?;
+    let mut config = state.config.lock()?;
+    let (base, mut colors) = match UserTheme::load(&shown) {
+        Ok(current) => (current.base, current.colors),
+        Err(_) => (shown, Default::default()),
+    };
+    colors.extend(config.theme.custom_colors.clone());
+
+    let theme = UserTheme {
+        id: crate::template::slugify(name),
+        name: name.trim().to_string(),
+        base,
+        colors,
+        extra: Default::default(),
+    };
+    theme.save()?;
+
+    config.theme.preset = theme.id.clone();
+    config.theme.mode = ThemeSettings::default().mode;
+    config.theme.custom_colors.clear();
+    config.save()?;
+    drop(config);
+    crate::gui::update_active_theme(&app);
+    Ok(theme)
+}
+
+/// Delete a user theme, falling back to the default presets where it is
+/// in use
 #[tauri::command]
-pub fn get_theme_settings(state: State<AppState>) -> Result<ThemeSettings, String> {
-    let config = state.config.lock().map_err(|e| e.to_string())?;
+pub fn delete_theme(app: AppHandle, state: State<AppState>, id: &str) -> Result<()> {
+    UserTheme::delete(id)?;
+    let mut config = state.config.lock()?;
+    let defaults = ThemeSettings::default();
+    let theme = &mut config.theme;
+    let mut changed = false;
+    for (preset, default) in [
+        (&mut theme.preset, defaults.preset),
+        (&mut theme.light_preset, defaults.light_preset),
+        (&mut theme.dark_preset, defaults.dark_preset),
+    ] {
+        if preset == id {
+            *preset = default;
+            changed = true;
+        }
+    }
+    if changed {
+        config.save()?;
+        drop(config);
+        crate::gui::update_active_theme(&app);
+    }
+    Ok(())
+}
+
+/// The preset the theme mode picks right now
+pub(crate) fn active_theme(state: &AppState) -> Result<String> {
+    let system_dark = *state.system_dark.lock()?;
+    let config = state.config.lock()?;
+    Ok(config.theme.active_preset(system_dark, Local::now().time()).to_string())
+}
+
+/// The preset to show, which changes with the desktop's color scheme or
+/// the schedule when the theme mode is "system" or "schedule"
+#[tauri::command]
+pub fn get_active_theme(state: State<AppState>) -> Result<String> {
+    active_theme(&state)
+}
+
+#[tauri::command]
+pub fn get_theme_settings(state: State<AppState>) -> Result<ThemeSettings> {
+    let config = state.config.lock()?;
     Ok(config.theme.clone())
 }
 
 #[tauri::command]
-pub fn set_theme_preset(state: State<AppState>, preset: String) -> Result<(), String> {
-    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+pub fn set_theme_preset(app: AppHandle, state: State<AppState>, preset: String) -> Result<()> {
+    ThemeSettings::validate_preset(&preset)?;
+    let mut config = state.config.lock()?;
     config.theme.preset = preset;
-    config.save().map_err(|e| e.to_string())
+    config.save()?;
+    drop(config);
+    crate::gui::update_active_theme(&app);
+    Ok(())
 }
 
 #[tauri::command]
-pub fn set_custom_colors(state: State<AppState>, colors: HashMap<String, String>) -> Result<(), String> {
-    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+pub fn set_custom_colors(state: State<AppState>, colors: HashMap<String, String>) -> Result<()> {
+    for (name, value) in &colors {
+        ThemeSettings::validate_color(name, value)?;
+    }
+    let mut config = state.config.lock()?;
     config.theme.custom_colors = colors;
-    config.save().map_err(|e| e.to_string())
+    config.save()
 }
 
 #[tauri::command]
-pub fn set_font_settings(state: State<AppState>, font_family: String, font_size: u32, mono_font: String) -> Result<(), String> {
-    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+pub fn set_font_settings(state: State<AppState>, font_family: String, font_size: u32, mono_font: String) -> Result<()> {
+    ThemeSettings::validate_font_size(font_size)?;
+    let mut config = state.config.lock()?;
     config.theme.font_family = font_family;
     config.theme.font_size = font_size;
     config.theme.mono_font = mono_font;
-    config.save().map_err(|e| e.to_string())
+    config.save()
 }
 
 #[tauri::command]
-pub fn save_theme_settings(state: State<AppState>, settings: ThemeSettings) -> Result<(), String> {
-    let mut config = state.config.lock().map_err(|e| e.to_string())?;
+pub fn save_theme_settings(app: AppHandle, state: State<AppState>, mut settings: ThemeSettings) -> Result<()> {
+    settings.validate()?;
+    let mut config = state.config.lock()?;
+    settings.extra = std::mem::take(&mut config.theme.extra);
     config.theme = settings;
-    config.save().map_err(|e| e.to_string())
+    config.save()?;
+    drop(config);
+    crate::gui::update_active_theme(&app);
+    Ok(())
 }
 
 // Window controls
 #[tauri::command]
-pub fn window_start_drag(app: AppHandle) -> Result<(), String> {
+pub fn window_start_drag(app: AppHandle) -> Result<()> {
     if let Some(window) = app.get_webview_window("main") {
-        window.start_dragging().map_err(|e| e.to_string())
+        window.start_dragging().map_err(|e| KiroError::Window(e.to_string()))
     } else {
-        Err("Window not found".to_string())
+        Err(KiroError::Unavailable("Main window".into()))
     }
 }
 
 #[tauri::command]
-pub fn window_minimize(app: AppHandle) -> Result<(), String> {
+pub fn window_minimize(app: AppHandle) -> Result<()> {
     if let Some(window) = app.get_webview_window("main") {
-        window.minimize().map_err(|e| e.to_string())
+        window.minimize().map_err(|e| KiroError::Window(e.to_string()))
     } else {
-        Err("Window not found".to_string())
+        Err(KiroError::Unavailable("Main window".into()))
     }
 }
 
 #[tauri::command]
-pub fn window_maximize(app: AppHandle) -> Result<(), String> {
+pub fn window_maximize(app: AppHandle) -> Result<()> {
     if let Some(window) = app.get_webview_window("main") {
         if window.is_maximized().unwrap_or(false) {
-            window.unmaximize().map_err(|e| e.to_string())
+            window.unmaximize().map_err(|e| KiroError::Window(e.to_string()))
         } else {
-            window.maximize().map_err(|e| e.to_string())
+            window.maximize().map_err(|e| KiroError::Window(e.to_string()))
         }
     } else {
-        Err("Window not found".to_string())
+        Err(KiroError::Unavailable("Main window".into()))
     }
 }
 
 #[tauri::command]
-pub fn window_close(app: AppHandle) -> Result<(), String> {
+pub fn window_close(app: AppHandle) -> Result<()> {
     if let Some(window) = app.get_webview_window("main") {
-        window.close().map_err(|e| e.to_string())
+        window.close().map_err(|e| KiroError::Window(e.to_string()))
     } else {
-        Err("Window not found".to_string())
+        Err(KiroError::Unavailable("Main window".into()))
     }
 }
@@ -0,0 +1,305 @@
+//! Key chords for the app's keyboard actions. `config.json` only stores the
+//! actions a user has rebound; the rest keep their defaults.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::Config;
+use crate::error::{KiroError, Result};
+
+/// Chords for each action name
+pub type Keymap = BTreeMap<String, Vec<String>>;
+
+/// Every action with its default, Vim-style chords
+pub const DEFAULT_KEYBINDINGS: &[(&str, &[&str])] = &[
+    ("move_down", &["j", "ArrowDown"]),
+    ("move_up", &["k", "ArrowUp"]),
+    ("jump_first", &["g"]),
+    ("jump_last", &["G"]),
+    ("page_down", &["Ctrl+d"]),
+    ("page_up", &["Ctrl+u"]),
+    ("toggle_focus", &["Tab"]),
+    ("open_note", &["Enter"]),
+    ("new_from_template", &["t"]),
+    ("daily_note", &["Ctrl+j"]),
+    ("toggle_mark", &["Space"]),
+    ("mark_all", &["a"]),
+    ("clear_marks", &["c"]),
+    ("delete_note", &["d"]),
+    ("toggle_secret", &["s"]),
+    ("import", &["F2"]),
+    ("export_marked", &["F3"]),
+    ("save_note", &["Ctrl+s"]),
+    ("previous_day", &["Alt+ArrowLeft"]),
+    ("next_day", &["Alt+ArrowRight"]),
+    ("cancel", &["Escape"]),
+    ("open_settings", &["Ctrl+,"]),
+    ("show_help", &["?"]),
+    ("quit", &["Ctrl+q"]),
+];
+
+/// Keys that aren't a single character, as named by `KeyboardEvent.key`
+const NAMED_KEYS: &[&str] = &[
+    "Enter", "Escape", "Tab", "Space", "Backspace", "Delete", "Insert", "Home", "End", "PageUp", "PageDown",
+    "ArrowUp", "ArrowDown", "ArrowLeft", "ArrowRight", "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9",
+    "F10", "F11", "F12",
+];
+
+/// Profiles are stored as ~/.config/kiro/keymaps/<name>.json
+const PROFILE_EXTENSION: &str = "json";
+
+pub fn is_action(name: &str) -> bool {
+    DEFAULT_KEYBINDINGS.iter().any(|(action, _)| *action == name)
+}
+
+/// The default keymap with `overrides` applied. Actions this version
+/// doesn't know are left out.
+pub fn resolve(overrides: &Keymap) -> Keymap {
+    DEFAULT_KEYBINDINGS
+        .iter()
+        .map(|(action, chords)| {
+            let chords = overrides
+                .get(*action)
+                .cloned()
+                .unwrap_or_else(|| chords.iter().map(|c| c.to_string()).collect());
+            (action.to_string(), chords)
+        })
+        .collect()
+}
+
+/// Check that `action` exists and put each chord in normal form, see
+/// `normalize_chord`
+pub fn normalize_chords(action: &str, chords: &[String]) -> Result<Vec<String>> {
+    let field = format!("keybindings.{}", action);
+    if !is_action(action) {
+        return Err(KiroError::validation(field, format!("unknown action '{}'", action)));
+    }
+
+    let mut normalized: Vec<String> = Vec::with_capacity(chords.len());
+    for chord in chords {
+        let chord = normalize_chord(chord).map_err(|message| KiroError::validation(&field, message))?;
+        if !normalized.contains(&chord) {
+            normalized.push(chord);
+        }
+    }
+    Ok(normalized)
+}
+
+/// Normalize every action in `keymap`, failing on unknown actions
+pub fn normalize(keymap: &Keymap) -> Result<Keymap> {
+    keymap
+        .iter()
+        .map(|(action, chords)| Ok((action.clone(), normalize_chords(action, chords)?)))
+        .collect()
+}
+
+/// One chord bound to two actions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub chord: String,
+    pub action: String,
+    /// The action the chord was already bound to
+    pub other: String,
+}
+
+/// The first chord that applying `overrides` would bind to two actions
+pub fn find_conflict(overrides: &Keymap) -> Option<Conflict> {
+    let mut bound: BTreeMap<&str, &str> = BTreeMap::new();
+    let keymap = resolve(overrides);
+    for (action, chords) in &keymap {
+        for chord in chords {
+            if let Some(other) = bound.insert(chord, action) {
+                return Some(Conflict {
+                    chord: chord.clone(),
+                    action: action.clone(),
+                    other: other.to_string(),
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Fail if applying `overrides` would bind one chord to two actions
+pub fn check_conflicts(overrides: &Keymap) -> Result<()> {
+    match find_conflict(overrides) {
+        Some(conflict) => Err(KiroError::validation(
+            format!("keybindings.{}", conflict.action),
+            format!("{} is already bound to {}", conflict.chord, conflict.other),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Unbind chords until no chord belongs to two actions, returning each
+/// removal with `action` as the action that lost the chord. A chord the
+/// user bound wins over a default, such as one a newer version added, so
+/// only the clashing chord is lost rather than the user's keymap.
+pub fn remove_conflicts(overrides: &mut Keymap) -> Vec<Conflict> {
+    let mut removed = Vec::new();
+    while let Some(mut conflict) = find_conflict(overrides) {
+        if overrides.contains_key(&conflict.action) && !overrides.contains_key(&conflict.other) {
+            std::mem::swap(&mut conflict.action, &mut conflict.other);
+        }
+        let mut chords = resolve(overrides).remove(&conflict.action).unwrap_or_default();
+        chords.retain(|chord| *chord != conflict.chord);
+        overrides.insert(conflict.action.clone(), chords);
+        removed.push(conflict);
+    }
+    removed
+}
+
+/// Put a chord like `ctrl+shift+d` in the form the frontend builds from key
+/// events: modifiers in `Ctrl+Alt+Shift+Meta` order, then the key. A
+/// character on its own stands for itself, so `G` is Shift+g; with Ctrl,
+/// Alt or Meta a letter is lowercase unless Shift is given.
+fn normalize_chord(chord: &str) -> std::result::Result<String, String> {
+    let chord = chord.trim();
+    let (modifiers, key) = match chord.strip_suffix("++") {
+        Some(modifiers) => (modifiers, "+"),
+        None if chord == "+" => ("", "+"),
+        None => chord.rsplit_once('+').unwrap_or(("", chord)),
+    };
+
+    let (mut ctrl, mut alt, mut shift, mut meta) = (false, false, false, false);
+    for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+        match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => ctrl = true,
+            "alt" | "option" => alt = true,
+            "shift" => shift = true,
+            "meta" | "cmd" | "super" => meta = true,
+            _ => return Err(format!("unknown modifier '{}' in '{}'", modifier, chord)),
+        }
+    }
+
+    let mut chars = key.chars();
+    let key = match (chars.next(), chars.next()) {
+        (None, _) => return Err(format!("'{}' has no key", chord)),
+        (Some(' '), None) => "Space".to_string(),
+        (Some(c), None) if c.is_alphabetic() => {
+            let upper = shift || (!ctrl && !alt && !meta && c.is_uppercase());
+            // Shift is part of the character
+            shift = false;
+            if upper {
+                c.to_uppercase().collect()
+            } else {
+                c.to_lowercase().collect()
+            }
+        }
+        (Some(c), None) => {
+            if shift {
+                return Err(format!(
+                    "write the character Shift produces instead of Shift+{} in '{}'",
+                    c, chord
+                ));
+            }
+            c.to_string()
+        }
+        _ => NAMED_KEYS
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(key))
+            .map(|name| name.to_string())
+            .ok_or_else(|| format!("unknown key '{}' in '{}'", key, chord))?,
+    };
+
+    let mut normalized = String::new();
+    for (on, name) in [(ctrl, "Ctrl+"), (alt, "Alt+"), (shift, "Shift+"), (meta, "Meta+")] {
+        if on {
+            normalized.push_str(name);
+        }
+    }
+    normalized.push_str(&key);
+    Ok(normalized)
+}
+
+/// Directory of saved keymap profiles
+pub fn profiles_dir() -> Option<PathBuf> {
+    Config::config_dir().map(|p| p.join("keymaps"))
+}
+
+fn profile_path(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(KiroError::validation("name", format!("invalid profile name '{}'", name)));
+    }
+    let dir = profiles_dir().ok_or_else(|| KiroError::InvalidPath("No config directory".to_string()))?;
+    Ok(dir.join(format!("{}.{}", name, PROFILE_EXTENSION)))
+}
+
+/// Names of all saved profiles, sorted
+pub fn list_profiles() -> Vec<String> {
+    let Some(dir) = profiles_dir() else {
+        return Vec::new();
+    };
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = read_dir
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(PROFILE_EXTENSION))
+        .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(String::from))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Read a profile, normalized and checked for conflicts, with a warning
+/// for each action that was skipped
+pub fn load_profile(name: &str) -> Result<(Keymap, Vec<String>)> {
+    let path = profile_path(name)?;
+    let contents = fs::read_to_string(&path)
+        .map_err(|_| KiroError::validation("name", format!("no keymap profile named '{}'", name)))?;
+    parse_profile(&contents)
+}
+
+/// Skip actions this version doesn't know, such as ones from a profile
+/// saved by a newer version, and actions with a chord that can't be used,
+/// so one of them doesn't cost the rest of the profile
+fn parse_profile(contents: &str) -> Result<(Keymap, Vec<String>)> {
+    let profile: Keymap = serde_json::from_str(contents)?;
+    let mut warnings = Vec::new();
+    let mut keymap = Keymap::new();
+    for (action, chords) in &profile {
+        match normalize_chords(action, chords) {
+            Ok(normalized) => {
+                keymap.insert(action.clone(), normalized);
+            }
+            Err(e) if is_action(action) => warnings.push(format!("{}; using the default keys", e)),
+            Err(e) => warnings.push(format!("{}; skipping it", e)),
+        }
+    }
+    check_conflicts(&keymap)?;
+    Ok((keymap, warnings))
+}
+
+/// Write `keymap` as a profile, replacing any profile with the same name
+pub fn save_profile(name: &str, keymap: &Keymap) -> Result<PathBuf> {
+    let path = profile_path(name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(keymap)?)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_skips_what_it_cant_use() {
+        let contents = r#"{"move_down": ["Alt+j", "ArrowDown"], "open_graph": ["g"], "page_up": ["Hyper+u"]}"#;
+        let (keymap, warnings) = parse_profile(contents).unwrap();
+        assert_eq!(keymap.len(), 1);
+        assert_eq!(keymap["move_down"], ["Alt+j", "ArrowDown"]);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().any(|w| w.contains("unknown action 'open_graph'")));
+        assert!(warnings.iter().any(|w| w.contains("keybindings.page_up")));
+
+        // A clash is still refused rather than guessed at
+        let clash = r#"{"move_down": ["d"], "move_up": ["d"]}"#;
+        assert!(parse_profile(clash).is_err());
+    }
+}
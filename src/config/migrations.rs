@@ -0,0 +1,38 @@
+use serde_json::{Map, Value};
+
+/// One step of the config.json history. `up` works on the raw JSON so a
+/// renamed or moved key can be carried over before the file is parsed.
+struct Migration {
+    version: u32,
+    up: fn(&mut Map<String, Value>),
+}
+
+/// Every layout change, oldest first. Append new steps here; never edit
+/// a step that has shipped.
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    up: add_version,
+}];
+
+/// Current config.json layout, stored in its `version` key
+pub const CONFIG_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Layout version of a config object; files from before versioning have none
+pub(super) fn file_version(config: &Map<String, Value>) -> u32 {
+    config
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(0, |version| version as u32)
+}
+
+/// Bring a config object up to `CONFIG_VERSION`
+pub(super) fn migrate(config: &mut Map<String, Value>) {
+    let current = file_version(config);
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        (migration.up)(config);
+        config.insert("version".to_string(), migration.version.into());
+    }
+}
+
+/// v1: files written before versioning; the layout itself is unchanged
+fn add_version(_config: &mut Map<String, Value>) {}
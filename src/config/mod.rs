@@ -0,0 +1,1162 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::io::ErrorKind;
+use std::fs::OpenOptions;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::error::{KiroError, Result};
+
+pub mod keybindings;
+mod migrations;
+pub mod themes;
+
+pub use keybindings::Keymap;
+pub use migrations::CONFIG_VERSION;
+
+/// Built-in theme presets, matching the `data-theme` blocks in theme.css
+pub const THEME_PRESETS: &[&str] = &[
+    "dark",
+    "light",
+    "nord",
+    "solarized-dark",
+    "dracula",
+    "monokai",
+    "ocean",
+    "high-contrast",
+];
+
+/// CSS variables a theme or custom color may set, matching the colors in
+/// each `data-theme` block in theme.css
+pub const COLOR_VARIABLES: &[&str] = &[
+    "--bg-primary",
+    "--bg-secondary",
+    "--bg-tertiary",
+    "--bg-input",
+    "--bg-hover",
+    "--bg-selected",
+    "--bg-marked",
+    "--text-primary",
+    "--text-secondary",
+    "--text-muted",
+    "--accent",
+    "--accent-hover",
+    "--accent-dim",
+    "--success",
+    "--warning",
+    "--error",
+    "--border",
+    "--border-light",
+    "--border-focus",
+];
+
+/// How the preset is chosen: always `preset` ("fixed"), `light_preset` or
+/// `dark_preset` following the desktop's color scheme ("system"), or by
+/// time of day ("schedule")
+pub const THEME_MODES: &[&str] = &["fixed", "system", "schedule"];
+
+/// Font sizes offered in the settings panel, in pixels
+pub const FONT_SIZES: RangeInclusive<u32> = 10..=24;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemeSettings {
+    /// Current theme preset name
+    #[serde(default = "default_theme")]
+    pub preset: String,
+
+    /// One of `THEME_MODES`
+    #[serde(default = "default_theme_mode")]
+    pub mode: String,
+
+    /// Preset for a light desktop or daytime in the "system" and
+    /// "schedule" modes
+    #[serde(default = "default_light_preset")]
+    pub light_preset: String,
+
+    /// Preset for a dark desktop or nighttime
+    #[serde(default = "default_theme")]
+    pub dark_preset: String,
+
+    /// Local time (HH:MM) the "schedule" mode switches to `dark_preset`
+    #[serde(default = "default_dark_start")]
+    pub dark_start: String,
+
+    /// Local time (HH:MM) it switches back to `light_preset`
+    #[serde(default = "default_light_start")]
+    pub light_start: String,
+
+    /// Custom color overrides (CSS variable name -> color value)
+    #[serde(default)]
+    pub custom_colors: HashMap<String, String>,
+
+    /// Font family
+    #[serde(default = "default_font_family")]
+    pub font_family: String,
+
+    /// Font size in pixels
+    #[serde(default = "default_font_size")]
+    pub font_size: u32,
+
+    /// Monospace font family
+    #[serde(default = "default_mono_font")]
+    pub mono_font: String,
+
+    /// Unknown keys, see `Config::extra`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn default_theme() -> String {
+    "dark".to_string()
+}
+
+fn default_theme_mode() -> String {
+    "fixed".to_string()
+}
+
+fn default_light_preset() -> String {
+    "light".to_string()
+}
+
+fn default_dark_start() -> String {
+    "19:00".to_string()
+}
+
+fn default_light_start() -> String {
+    "07:00".to_string()
+}
+
+fn default_font_family() -> String {
+    "Inter, system-ui, -apple-system, sans-serif".to_string()
+}
+
+fn default_font_size() -> u32 {
+    16
+}
+
+fn default_mono_font() -> String {
+    "JetBrains Mono, Fira Code, Consolas, monospace".to_string()
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            preset: default_theme(),
+            mode: default_theme_mode(),
+            light_preset: default_light_preset(),
+            dark_preset: default_theme(),
+            dark_start: default_dark_start(),
+            light_start: default_light_start(),
+            custom_colors: HashMap::new(),
+            font_family: default_font_family(),
+            font_size: default_font_size(),
+            mono_font: default_mono_font(),
+            extra: Map::new(),
+        }
+    }
+}
+
+impl ThemeSettings {
+    /// A built-in preset or the id of a theme in the themes directory
+    pub fn validate_preset(preset: &str) -> Result<()> {
+        check_preset("theme.preset", preset)
+    }
+
+    pub fn validate_mode(mode: &str) -> Result<()> {
+        if THEME_MODES.contains(&mode) {
+            Ok(())
+        } else {
+            Err(KiroError::validation(
+                "theme.mode",
+                format!("'{}' is not one of {}", mode, THEME_MODES.join(", ")),
+            ))
+        }
+    }
+
+    /// The light and dark presets of the automatic modes
+    pub fn validate_auto_presets(light: &str, dark: &str) -> Result<()> {
+        check_preset("theme.light_preset", light)?;
+        check_preset("theme.dark_preset", dark)
+    }
+
+    /// Both schedule times must be HH:MM and differ
+    pub fn validate_schedule(dark_start: &str, light_start: &str) -> Result<()> {
+        let parse = |field: &str, value: &str| {
+            parse_time(value)
+                .ok_or_else(|| KiroError::validation(field, format!("'{}' is not a time like 19:30", value)))
+        };
+        let dark = parse("theme.dark_start", dark_start)?;
+        let light = parse("theme.light_start", light_start)?;
+        if dark == light {
+            return Err(KiroError::validation("theme.dark_start", "must differ from light_start"));
+        }
+        Ok(())
+    }
+
+    /// The preset to show at `now`. `system_dark` is whether the desktop
+    /// prefers dark colors; while that is unknown the "system" mode shows
+    /// `preset`.
+    pub fn active_preset(&self, system_dark: Option<bool>, now: NaiveTime) -> &str {
+        let dark = match self.mode.as_str() {
+            "system" => system_dark,
+            "schedule" => match (parse_time(&self.dark_start), parse_time(&self.light_start)) {
+                (Some(dark), Some(light)) if dark < light => Some(now >= dark && now < light),
+                // The dark period runs over midnight
+                (Some(dark), Some(light)) => Some(now >= dark || now < light),
+                _ => None,
+            },
+            _ => None,
+        };
+        match dark {
+            Some(true) => &self.dark_preset,
+            Some(false) => &self.light_preset,
+            None => &self.preset,
+        }
+    }
+
+    pub fn validate_font_size(size: u32) -> Result<()> {
+        if FONT_SIZES.contains(&size) {
+            Ok(())
+        } else {
+            Err(KiroError::validation(
+                "theme.font_size",
+                format!("{} is not between {} and {}", size, FONT_SIZES.start(), FONT_SIZES.end()),
+            ))
+        }
+    }
+
+    /// Custom colors override CSS variables, so the name must be one of
+    /// `COLOR_VARIABLES` and the value a hex, `rgb()`, `hsl()` or named color
+    pub fn validate_color(name: &str, value: &str) -> Result<()> {
+        check_color(name, value).map_err(|message| KiroError::validation(format!("theme.custom_colors.{}", name), message))
+    }
+
+    /// Check every setting, failing on the first invalid one
+    pub fn validate(&self) -> Result<()> {
+        Self::validate_preset(&self.preset)?;
+        Self::validate_mode(&self.mode)?;
+        Self::validate_auto_presets(&self.light_preset, &self.dark_preset)?;
+        Self::validate_schedule(&self.dark_start, &self.light_start)?;
+        Self::validate_font_size(self.font_size)?;
+        for (name, value) in &self.custom_colors {
+            Self::validate_color(name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A built-in preset or the id of a theme in the themes directory
+fn check_preset(field: &str, preset: &str) -> Result<()> {
+    if THEME_PRESETS.contains(&preset) || themes::UserTheme::load(preset).is_ok() {
+        Ok(())
+    } else {
+        Err(KiroError::validation(field, format!("unknown theme '{}'", preset)))
+    }
+}
+
+/// A time of day written as HH:MM
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// Why `name: value` can't be used as a theme color, if it can't
+pub(crate) fn check_color(name: &str, value: &str) -> std::result::Result<(), String> {
+    if !COLOR_VARIABLES.contains(&name) {
+        return Err(format!("'{}' is not a theme color variable", name));
+    }
+    if !is_css_color(value) {
+        return Err(format!("'{}' is not a color", value));
+    }
+    Ok(())
+}
+
+/// `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, an `rgb()`/`rgba()`/`hsl()`/`hsla()`
+/// call in comma or space syntax, or a CSS color name
+fn is_css_color(value: &str) -> bool {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+
+    let Some((function, rest)) = value.split_once('(') else {
+        return NAMED_COLORS.contains(&value.to_ascii_lowercase().as_str());
+    };
+    let Some((channels, alpha)) = rest.strip_suffix(')').and_then(color_arguments) else {
+        return false;
+    };
+    let alpha_ok = alpha.is_none_or(is_number_or_percentage);
+    match function.to_ascii_lowercase().as_str() {
+        "rgb" | "rgba" => alpha_ok && channels.iter().all(|c| is_number_or_percentage(c)),
+        "hsl" | "hsla" => {
+            alpha_ok && is_angle(channels[0]) && channels[1..].iter().all(|c| is_number_or_percentage(c))
+        }
+        _ => false,
+    }
+}
+
+/// The three channels and optional alpha of `a, b, c[, alpha]` or
+/// `a b c[ / alpha]`
+fn color_arguments(args: &str) -> Option<([&str; 3], Option<&str>)> {
+    let (parts, alpha): (Vec<&str>, Option<&str>) = if args.contains(',') {
+        let mut parts: Vec<&str> = args.split(',').map(str::trim).collect();
+        let alpha = if parts.len() == 4 { parts.pop() } else { None };
+        (parts, alpha)
+    } else {
+        let (channels, alpha) = match args.split_once('/') {
+            Some((channels, alpha)) => (channels, Some(alpha.trim())),
+            None => (args, None),
+        };
+        (channels.split_whitespace().collect(), alpha)
+    };
+    let channels: [&str; 3] = parts.try_into().ok()?;
+    Some((channels, alpha))
+}
+
+/// `12`, `-1.5`, `.5`: no exponents, `inf` or `nan`
+fn is_number(value: &str) -> bool {
+    let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    (!whole.is_empty() || !fraction.is_empty())
+        && !digits.ends_with('.')
+        && whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+}
+
+fn is_number_or_percentage(value: &str) -> bool {
+    is_number(value.strip_suffix('%').unwrap_or(value))
+}
+
+/// A hue: a number, optionally in `deg`, `rad`, `grad` or `turn`
+fn is_angle(value: &str) -> bool {
+    let number = ["deg", "grad", "rad", "turn"]
+        .iter()
+        .find_map(|unit| value.strip_suffix(unit))
+        .unwrap_or(value);
+    is_number(number)
+}
+
+/// CSS named colors, plus `transparent` and `currentcolor`
+const NAMED_COLORS: &[&str] = &[
+    "aliceblue", "antiquewhite", "aqua", "aquamarine", "azure", "beige", "bisque", "black",
+    "blanchedalmond", "blue", "blueviolet", "brown", "burlywood", "cadetblue", "chartreuse",
+    "chocolate", "coral", "cornflowerblue", "cornsilk", "crimson", "cyan", "darkblue", "darkcyan",
+    "darkgoldenrod", "darkgray", "darkgreen", "darkgrey", "darkkhaki", "darkmagenta",
+    "darkolivegreen", "darkorange", "darkorchid", "darkred", "darksalmon", "darkseagreen",
+    "darkslateblue", "darkslategray", "darkslategrey", "darkturquoise", "darkviolet", "deeppink",
+    "deepskyblue", "dimgray", "dimgrey", "dodgerblue", "firebrick", "floralwhite", "forestgreen",
+    "fuchsia", "gainsboro", "ghostwhite", "gold", "goldenrod", "gray", "green", "greenyellow",
+    "grey", "honeydew", "hotpink", "indianred", "indigo", "ivory", "khaki", "lavender",
+    "lavenderblush", "lawngreen", "lemonchiffon", "lightblue", "lightcoral", "lightcyan",
+    "lightgoldenrodyellow", "lightgray", "lightgreen", "lightgrey", "lightpink", "lightsalmon",
+    "lightseagreen", "lightskyblue", "lightslategray", "lightslategrey", "lightsteelblue",
+    "lightyellow", "lime", "limegreen", "linen", "magenta", "maroon", "mediumaquamarine",
+    "mediumblue", "mediumorchid", "mediumpurple", "mediumseagreen", "mediumslateblue",
+    "mediumspringgreen", "mediumturquoise", "mediumvioletred", "midnightblue", "mintcream",
+    "mistyrose", "moccasin", "navajowhite", "navy", "oldlace", "olive", "olivedrab", "orange",
+    "orangered", "orchid", "palegoldenrod", "palegreen", "paleturquoise", "palevioletred",
+    "papayawhip", "peachpuff", "peru", "pink", "plum", "powderblue", "purple", "rebeccapurple",
+    "red", "rosybrown", "royalblue", "saddlebrown", "salmon", "sandybrown", "seagreen",
+    "seashell", "sienna", "silver", "skyblue", "slateblue", "slategray", "slategrey", "snow",
+    "springgreen", "steelblue", "tan", "teal", "thistle", "tomato", "turquoise", "violet",
+    "wheat", "white", "whitesmoke", "yellow", "yellowgreen", "transparent", "currentcolor",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanSettings {
+    /// Maximum directory depth below each scan root
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+
+    /// Files larger than this many bytes are skipped
+    #[serde(default = "default_max_file_size")]
+    pub max_file_size: u64,
+
+    /// Scanning stops after this many matching files
+    #[serde(default = "default_max_results")]
+    pub max_results: usize,
+
+    /// Honor .gitignore and .kiroignore files found while scanning
+    #[serde(default = "default_respect_ignore_files")]
+    pub respect_ignore_files: bool,
+
+    /// Gitignore-style patterns that are never scanned
+    #[serde(default = "default_exclude_patterns")]
+    pub exclude_patterns: Vec<String>,
+
+    /// Unknown keys, see `Config::extra`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn default_max_depth() -> usize {
+    12
+}
+
+fn default_max_file_size() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_max_results() -> usize {
+    5000
+}
+
+fn default_respect_ignore_files() -> bool {
+    true
+}
+
+fn default_exclude_patterns() -> Vec<String> {
+    vec!["node_modules/".to_string(), "target/".to_string()]
+}
+
+impl Default for ScanSettings {
+    fn default() -> Self {
+        Self {
+            max_depth: default_max_depth(),
+            max_file_size: default_max_file_size(),
+            max_results: default_max_results(),
+            respect_ignore_files: default_respect_ignore_files(),
+            exclude_patterns: default_exclude_patterns(),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupSettings {
+    /// Take automatic backups in the background
+    #[serde(default = "default_backup_enabled")]
+    pub enabled: bool,
+
+    /// Hours between automatic backups
+    #[serde(default = "default_backup_interval")]
+    pub interval_hours: u32,
+
+    /// Number of automatic backups to keep
+    #[serde(default = "default_backup_keep")]
+    pub keep: usize,
+
+    /// Directory for automatic backups, defaults to `backups/` next to the
+    /// database. Each database gets its own folder inside, see `dir_for`.
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+
+    /// Unknown keys, see `Config::extra`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn default_backup_enabled() -> bool {
+    true
+}
+
+fn default_backup_interval() -> u32 {
+    24
+}
+
+fn default_backup_keep() -> usize {
+    7
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_backup_enabled(),
+            interval_hours: default_backup_interval(),
+            keep: default_backup_keep(),
+            directory: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+impl BackupSettings {
+    /// Directory for backups of the database at `db_path`, so that vaults
+    /// never rotate or list each other's backups: `backups/<stem>/` next to
+    /// the database, or `<stem>-<hash>/` in `directory` if set, where the
+    /// hash of the full path keeps same-named databases from different
+    /// folders apart
+    pub fn dir_for(&self, db_path: &Path) -> PathBuf {
+        let stem = db_path
+            .file_stem()
+            .map_or_else(|| "notes".into(), |stem| stem.to_string_lossy());
+        match &self.directory {
+            Some(directory) => {
+                let hash = Sha256::digest(db_path.to_string_lossy().as_bytes());
+                let suffix: String = hash[..4].iter().map(|b| format!("{:02x}", b)).collect();
+                directory.join(format!("{}-{}", stem, suffix))
+            }
+            None => db_path
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join("backups")
+                .join(stem.as_ref()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecuritySettings {
+    /// Minutes without activity before encrypted notes are locked, 0 to never lock
+    #[serde(default = "default_auto_lock_minutes")]
+    pub auto_lock_minutes: u32,
+
+    /// Unknown keys, see `Config::extra`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn default_auto_lock_minutes() -> u32 {
+    15
+}
+
+impl Default for SecuritySettings {
+    fn default() -> Self {
+        Self {
+            auto_lock_minutes: default_auto_lock_minutes(),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiSettings {
+    /// Serve the HTTP API on localhost while the app is running
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Port to listen on (127.0.0.1 only)
+    #[serde(default = "default_api_port")]
+    pub port: u16,
+
+    /// Bearer token clients must send, generated when the server first starts
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// Unknown keys, see `Config::extra`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn default_api_port() -> u16 {
+    7373
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_api_port(),
+            token: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalSettings {
+    /// strftime format for the title of a new daily note
+    #[serde(default = "default_journal_title")]
+    pub title_format: String,
+
+    /// Note template for the body of a new daily note
+    #[serde(default)]
+    pub template: Option<String>,
+
+    /// Unknown keys, see `Config::extra`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn default_journal_title() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+impl Default for JournalSettings {
+    fn default() -> Self {
+        Self {
+            title_format: default_journal_title(),
+            template: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+impl JournalSettings {
+    /// The format may use date and time fields, but not a time zone
+    pub fn validate_title_format(format: &str) -> Result<()> {
+        match format_date(format, NaiveDate::MIN) {
+            Some(title) if !title.trim().is_empty() => Ok(()),
+            _ => Err(KiroError::validation(
+                "journal.title_format",
+                format!("'{}' is not a date format like %Y-%m-%d", format),
+            )),
+        }
+    }
+
+    /// Title of a new daily note for `date`
+    pub fn title(&self, date: NaiveDate) -> String {
+        format_date(&self.title_format, date).unwrap_or_else(|| date.to_string())
+    }
+}
+
+/// `date` at midnight formatted with `format`, or None if the format is
+/// invalid
+fn format_date(format: &str, date: NaiveDate) -> Option<String> {
+    use std::fmt::Write;
+    let mut text = String::new();
+    write!(text, "{}", date.and_time(NaiveTime::MIN).format(format)).ok()?;
+    Some(text)
+}
+
+/// Name of the vault at the default database location, which is always
+/// available and can't be closed
+pub const DEFAULT_VAULT: &str = "default";
+
+/// A named notes database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vault {
+    pub name: String,
+
+    /// Path of the vault's SQLite database
+    pub path: PathBuf,
+
+    /// Unknown keys, see `Config::extra`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Vault {
+    pub fn new(name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            path: path.into(),
+            extra: Map::new(),
+        }
+    }
+
+    /// Explain that the vault's database file is gone
+    pub fn missing_message(&self) -> String {
+        format!(
+            "the database of vault '{}' is missing at {}; move it back or close the vault",
+            self.name,
+            self.path.display()
+        )
+    }
+}
+
+/// A problem found while loading `config.json`
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigDiagnostic {
+    /// Setting the problem is in, e.g. `theme.font_size`; none for the whole file
+    pub field: Option<String>,
+    pub message: String,
+    /// Position of a syntax error in the file
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl ConfigDiagnostic {
+    /// Describe `error` followed by what was done about it
+    pub(crate) fn from_error(error: &KiroError, action: &str) -> Self {
+        let (field, line, column) = match error {
+            KiroError::Validation { field, .. } => (Some(field.clone()), None, None),
+            KiroError::ConfigParse { line, column, .. } => (None, Some(*line), Some(*column)),
+            _ => (None, None, None),
+        };
+        Self {
+            field,
+            message: format!("{}; {}", error, action),
+            line,
+            column,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    /// Layout version of config.json, see `CONFIG_VERSION`
+    #[serde(default)]
+    pub version: u32,
+
+    /// Directories selected for text file scanning
+    #[serde(default)]
+    pub scan_directories: HashSet<PathBuf>,
+
+    /// Limits and exclusions applied when scanning directories
+    #[serde(default)]
+    pub scan: ScanSettings,
+
+    /// Theme and appearance settings
+    #[serde(default)]
+    pub theme: ThemeSettings,
+
+    /// Automatic database backups
+    #[serde(default)]
+    pub backup: BackupSettings,
+
+    /// Encryption and auto-lock
+    #[serde(default)]
+    pub security: SecuritySettings,
+
+    /// Local HTTP API for scripts and editor plugins
+    #[serde(default)]
+    pub api: ApiSettings,
+
+    /// Daily notes
+    #[serde(default)]
+    pub journal: JournalSettings,
+
+    /// Notes databases besides the default one
+    #[serde(default)]
+    pub vaults: Vec<Vault>,
+
+    /// Vault opened on startup; the default database when unset
+    #[serde(default)]
+    pub current_vault: Option<String>,
+
+    /// Chords for the actions the user rebound, see `keybindings`
+    #[serde(default)]
+    pub keybindings: Keymap,
+
+    /// Keys this version doesn't know, kept so that saving doesn't drop
+    /// settings written by a newer version
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            scan_directories: HashSet::new(),
+            scan: ScanSettings::default(),
+            theme: ThemeSettings::default(),
+            backup: BackupSettings::default(),
+            security: SecuritySettings::default(),
+            api: ApiSettings::default(),
+            journal: JournalSettings::default(),
+            vaults: Vec::new(),
+            current_vault: None,
+            keybindings: Keymap::new(),
+            extra: Map::new(),
+        }
+    }
+}
+
+/// Replace `path` with `contents` through a temporary file that only the
+/// owner can read, since the config holds the API token. The rename keeps
+/// those permissions on the new file.
+fn write_private(path: &Path, contents: &str) -> Result<()> {
+    let temp = path.with_extension("json.tmp");
+    match fs::remove_file(&temp) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(&temp)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+impl Config {
+    /// Get the config directory (~/.config/kiro)
+    pub fn config_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("kiro"))
+    }
+
+    /// Get the config file path (~/.config/kiro/config.json)
+    pub fn config_path() -> Option<PathBuf> {
+        Self::config_dir().map(|p| p.join("config.json"))
+    }
+
+    /// Load config from disk, falling back to defaults for anything that
+    /// can't be used and reporting each problem. A file that doesn't parse
+    /// is copied to `config.json.bak` so the next save can't lose it.
+    pub fn load() -> (Self, Vec<ConfigDiagnostic>) {
+        let Some(path) = Self::config_path() else {
+            return (Self::default(), Vec::new());
+        };
+
+        let mut diagnostics = Vec::new();
+        let mut config = match Self::read(&path) {
+            Ok((config, version)) if version < CONFIG_VERSION => {
+                // Keep the old file for an older build, then write the new layout
+                let backup = path.with_extension(format!("json.v{}.bak", version));
+                if let Err(e) = fs::copy(&path, &backup).map_err(KiroError::from).and_then(|_| config.save()) {
+                    diagnostics.push(ConfigDiagnostic::from_error(&e, "the upgraded settings were not saved"));
+                }
+                config
+            }
+            Ok((config, version)) => {
+                if version > CONFIG_VERSION {
+                    diagnostics.push(ConfigDiagnostic {
+                        field: Some("version".to_string()),
+                        message: format!(
+                            "written by a newer version of Kiro (version {}); settings this version doesn't know are kept but not used",
+                            version
+                        ),
+                        line: None,
+                        column: None,
+                    });
+                }
+                config
+            }
+            Err(e) => {
+                let backup = path.with_extension("json.bak");
+                let note = match fs::copy(&path, &backup) {
+                    Ok(_) => format!("using default settings; the file was copied to {}", backup.display()),
+                    Err(_) => "using default settings".to_string(),
+                };
+                diagnostics.push(ConfigDiagnostic::from_error(&e, &note));
+                Self::default()
+            }
+        };
+        diagnostics.extend(config.validate());
+        (config, diagnostics)
+    }
+
+    /// Parse a config file, upgrading it from older layouts. Returns the
+    /// config and the version the file was written with; a missing file
+    /// gives the defaults.
+    pub fn read(path: &Path) -> Result<(Self, u32)> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok((Self::default(), CONFIG_VERSION)),
+            Err(e) => return Err(e.into()),
+        };
+
+        let parse_error = |e: serde_json::Error| {
+            // serde_json appends the position to its message; keep it separate
+            let message = e.to_string();
+            let suffix = format!(" at line {} column {}", e.line(), e.column());
+            KiroError::ConfigParse {
+                path: path.to_path_buf(),
+                line: e.line(),
+                column: e.column(),
+                message: message.strip_suffix(&suffix).unwrap_or(&message).to_string(),
+            }
+        };
+
+        let mut object: Map<String, Value> = serde_json::from_str(&contents).map_err(parse_error)?;
+        let version = migrations::file_version(&object);
+        if version >= CONFIG_VERSION {
+            // Parse the text itself so that type errors point into the file
+            let config = serde_json::from_str(&contents).map_err(parse_error)?;
+            return Ok((config, version));
+        }
+
+        migrations::migrate(&mut object);
+        let config = serde_json::from_value(Value::Object(object)).map_err(parse_error)?;
+        Ok((config, version))
+    }
+
+    /// Reset invalid values to their defaults, returning a diagnostic for each
+    pub fn validate(&mut self) -> Vec<ConfigDiagnostic> {
+        self.repair(false)
+    }
+
+    /// A diagnostic for each invalid value, for a reload that keeps the
+    /// current settings instead of resetting anything
+    pub fn problems(&self) -> Vec<ConfigDiagnostic> {
+        self.clone().repair(true)
+    }
+
+    fn repair(&mut self, keep_current: bool) -> Vec<ConfigDiagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut reset = |error: KiroError, default: &dyn std::fmt::Display| {
+            let action = if keep_current {
+                "keeping the current settings".to_string()
+            } else {
+                format!("using {}", default)
+            };
+            diagnostics.push(ConfigDiagnostic::from_error(&error, &action));
+        };
+
+        if let Err(e) = ThemeSettings::validate_preset(&self.theme.preset) {
+            reset(e, &default_theme());
+            self.theme.preset = default_theme();
+        }
+        if let Err(e) = ThemeSettings::validate_mode(&self.theme.mode) {
+            reset(e, &default_theme_mode());
+            self.theme.mode = default_theme_mode();
+        }
+        if let Err(e) = check_preset("theme.light_preset", &self.theme.light_preset) {
+            reset(e, &default_light_preset());
+            self.theme.light_preset = default_light_preset();
+        }
+        if let Err(e) = check_preset("theme.dark_preset", &self.theme.dark_preset) {
+            reset(e, &default_theme());
+            self.theme.dark_preset = default_theme();
+        }
+        if let Err(e) = ThemeSettings::validate_schedule(&self.theme.dark_start, &self.theme.light_start) {
+            reset(e, &"19:00 to 07:00");
+            self.theme.dark_start = default_dark_start();
+            self.theme.light_start = default_light_start();
+        }
+        if let Err(e) = ThemeSettings::validate_font_size(self.theme.font_size) {
+            reset(e, &default_font_size());
+            self.theme.font_size = default_font_size();
+        }
+        self.theme
+            .custom_colors
+            .retain(|name, value| match ThemeSettings::validate_color(name, value) {
+                Ok(()) => true,
+                Err(e) => {
+                    reset(e, &"the theme's color");
+                    false
+                }
+            });
+
+        if self.backup.interval_hours == 0 {
+            reset(KiroError::validation("backup.interval_hours", "must be at least 1"), &default_backup_interval());
+            self.backup.interval_hours = default_backup_interval();
+        }
+        if self.backup.keep == 0 {
+            reset(KiroError::validation("backup.keep", "must be at least 1"), &default_backup_keep());
+            self.backup.keep = default_backup_keep();
+        }
+        if self.api.port == 0 {
+            reset(KiroError::validation("api.port", "must not be 0"), &default_api_port());
+            self.api.port = default_api_port();
+        }
+        if let Err(e) = JournalSettings::validate_title_format(&self.journal.title_format) {
+            reset(e, &default_journal_title());
+            self.journal.title_format = default_journal_title();
+        }
+
+        let mut names = HashSet::from([DEFAULT_VAULT.to_string()]);
+        self.vaults.retain(|vault| {
+            if vault.name.trim().is_empty() {
+                reset(KiroError::validation("vaults", "a vault has no name"), &"the remaining vaults");
+                false
+            } else if !names.insert(vault.name.clone()) {
+                let message = format!("'{}' is used by more than one vault", vault.name);
+                reset(KiroError::validation("vaults", message), &"the first one");
+                false
+            } else {
+                true
+            }
+        });
+        if let Some(name) = self.current_vault.clone() {
+            match self.find_vault(&name) {
+                None => {
+                    let message = format!("there is no vault named '{}'", name);
+                    reset(KiroError::validation("current_vault", message), &"the default database");
+                    self.current_vault = None;
+                }
+                // Kept, so that saving while a synced or removable drive
+                // is away doesn't forget it; `current_vault_path` falls
+                // back to the default database meanwhile
+                Some(vault) if !vault.path.exists() => {
+                    if !keep_current {
+                        let message = vault.missing_message();
+                        reset(KiroError::validation("current_vault", message), &"the default database until it is back");
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+
+        // Actions from a newer version are kept as they are
+        self.keybindings.retain(|action, chords| {
+            if !keybindings::is_action(action) {
+                return true;
+            }
+            match keybindings::normalize_chords(action, chords) {
+                Ok(normalized) => {
+                    *chords = normalized;
+                    true
+                }
+                Err(e) => {
+                    reset(e, &"the default keys");
+                    false
+                }
+            }
+        });
+        for conflict in keybindings::remove_conflicts(&mut self.keybindings) {
+            let message = format!("{} is also bound to {}", conflict.chord, conflict.other);
+            reset(
+                KiroError::validation(format!("keybindings.{}", conflict.action), message),
+                &format!("it only for {}", conflict.other),
+            );
+        }
+
+        diagnostics
+    }
+
+    /// Save config to disk. The file is written beside config.json and
+    /// renamed over it, so a crash mid-write can't leave it truncated.
+    pub fn save(&self) -> Result<()> {
+        if let Some(path) = Self::config_path() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let contents = serde_json::to_string_pretty(self)?;
+            write_private(&path, &contents)?;
+        }
+        Ok(())
+    }
+
+    /// Every vault, starting with the default database
+    pub fn all_vaults(&self) -> Vec<Vault> {
+        std::iter::once(Vault::new(DEFAULT_VAULT, crate::default_db_path()))
+            .chain(self.vaults.iter().cloned())
+            .collect()
+    }
+
+    pub fn find_vault(&self, name: &str) -> Option<Vault> {
+        self.all_vaults().into_iter().find(|vault| vault.name == name)
+    }
+
+    /// Database of the vault to open on startup. A vault whose database is
+    /// missing would be created empty, so the default one is used instead.
+    pub fn current_vault_path(&self) -> PathBuf {
+        self.current_vault
+            .as_deref()
+            .and_then(|name| self.find_vault(name))
+            .filter(|vault| vault.path.exists())
+            .map_or_else(crate::default_db_path, |vault| vault.path)
+    }
+
+    /// Check that a vault about to be added has a name and a database
+    /// that no other vault uses
+    pub fn validate_new_vault(&self, vault: &Vault) -> Result<()> {
+        if vault.name.trim().is_empty() {
+            return Err(KiroError::validation("name", "a vault needs a name"));
+        }
+        if !vault.path.is_absolute() {
+            return Err(KiroError::validation("path", "must be an absolute path"));
+        }
+        let vaults = self.all_vaults();
+        if vaults.iter().any(|v| v.name == vault.name) {
+            return Err(KiroError::validation("name", format!("there is already a vault named '{}'", vault.name)));
+        }
+        if let Some(existing) = vaults.iter().find(|v| v.path == vault.path) {
+            return Err(KiroError::validation(
+                "path",
+                format!("{} is already the '{}' vault", vault.path.display(), existing.name),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check if scan directories have been configured
+    pub fn has_scan_directories(&self) -> bool {
+        !self.scan_directories.is_empty()
+    }
+
+    /// Set scan directories and save
+    pub fn set_scan_directories(&mut self, dirs: HashSet<PathBuf>) -> Result<()> {
+        self.scan_directories = dirs;
+        self.save()
+    }
+
+    /// Clear scan directories and save
+    pub fn clear_scan_directories(&mut self) -> Result<()> {
+        self.scan_directories.clear();
+        self.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn saved_config_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("kiro-config-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+
+        // A world-readable file from an older version is replaced too
+        fs::write(&path, "{}").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        fs::write(path.with_extension("json.tmp"), "stale").unwrap();
+
+        write_private(&path, r#"{"api": {"token": "secret"}}"#).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(fs::read_to_string(&path).unwrap().contains("secret"));
+        assert!(!path.with_extension("json.tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn css_colors() {
+        for color in [
+            "#fff",
+            "#1e1e2e80",
+            "rgb(255, 0, 0)",
+            "rgba(255, 0, 0, 0.5)",
+            "rgb(100% 0% 0% / 50%)",
+            "hsl(200, 80%, 60%)",
+            "hsla(200deg, 80%, 60%, .5)",
+            "hsl(0.5turn 80% 60% / 0.5)",
+            "RebeccaPurple",
+            "transparent",
+        ] {
+            assert!(is_css_color(color), "{}", color);
+        }
+        for junk in [
+            "",
+            "#ggg",
+            "#12345",
+            "red;",
+            "notacolor",
+            "rgb()",
+            "rgb(1, 2)",
+            "rgb(1, 2, 3, 4, 5)",
+            "rgb(d, e, g)",
+            "rgb(1deg, 2, 3)",
+            "rgb(1, , 3)",
+            "rgb(1 2 3 4)",
+            "rgb(1, 2, 3 / 4)",
+            "hsl(1e5, 2%, 3%)",
+            "hsl(--, 2%, 3%)",
+            "hsl(deg, 2%, 3%)",
+            "rgb(1, 2, 3) red",
+            "url(x)",
+        ] {
+            assert!(!is_css_color(junk), "{}", junk);
+        }
+    }
+
+    #[test]
+    fn missing_vault_is_kept() {
+        let missing = std::env::temp_dir().join(format!("kiro-missing-vault-test-{}.db", std::process::id()));
+        let mut config = Config {
+            vaults: vec![Vault::new("work", missing)],
+            current_vault: Some("work".to_string()),
+            ..Config::default()
+        };
+
+        // Reported on startup, but not a reason to refuse a reload
+        assert!(config.problems().is_empty());
+        let diagnostics = config.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].field.as_deref(), Some("current_vault"));
+
+        assert_eq!(config.current_vault.as_deref(), Some("work"));
+        assert_eq!(config.current_vault_path(), crate::default_db_path());
+
+        config.current_vault = Some("gone".to_string());
+        config.validate();
+        assert_eq!(config.current_vault, None);
+    }
+}
@@ -0,0 +1,162 @@
+//! User themes: JSON files in ~/.config/kiro/themes that sit alongside the
+//! built-in presets. A theme starts from a preset and overrides some of its
+//! colors.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{check_color, Config, THEME_PRESETS};
+use crate::error::{KiroError, Result};
+use crate::template::slugify;
+
+const THEME_EXTENSION: &str = "json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserTheme {
+    /// File name without `.json`, stored in `ThemeSettings::preset`
+    #[serde(skip_deserializing)]
+    pub id: String,
+
+    /// Name shown in the theme picker, the id if empty
+    #[serde(default)]
+    pub name: String,
+
+    /// Built-in preset providing the colors this theme doesn't set
+    #[serde(default = "default_base")]
+    pub base: String,
+
+    /// CSS variable -> color value
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+
+    /// Unknown keys, see `Config::extra`
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+fn default_base() -> String {
+    "dark".to_string()
+}
+
+impl UserTheme {
+    /// User themes live in ~/.config/kiro/themes/<id>.json
+    pub fn dir() -> Option<PathBuf> {
+        Config::config_dir().map(|p| p.join("themes"))
+    }
+
+    fn path(id: &str) -> Result<PathBuf> {
+        if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+            return Err(KiroError::validation("id", format!("invalid theme id '{}'", id)));
+        }
+        let dir = Self::dir().ok_or_else(|| KiroError::InvalidPath("No config directory".to_string()))?;
+        Ok(dir.join(format!("{}.{}", id, THEME_EXTENSION)))
+    }
+
+    /// Check the base preset and every color
+    pub fn validate(&self) -> Result<()> {
+        if !THEME_PRESETS.contains(&self.base.as_str()) {
+            return Err(KiroError::validation("base", format!("unknown preset '{}'", self.base)));
+        }
+        for (name, value) in &self.colors {
+            check_color(name, value).map_err(|message| KiroError::validation(format!("colors.{}", name), message))?;
+        }
+        Ok(())
+    }
+
+    fn read(path: &Path, id: &str) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut theme: Self = serde_json::from_str(&contents)?;
+        // The id is the file name; one written into the file is ignored
+        theme.extra.remove("id");
+        theme.id = id.to_string();
+        if theme.name.trim().is_empty() {
+            theme.name = id.to_string();
+        }
+        theme.validate()?;
+        Ok(theme)
+    }
+
+    pub fn load(id: &str) -> Result<Self> {
+        let path = Self::path(id)?;
+        if !path.is_file() {
+            return Err(KiroError::validation("id", format!("no theme named '{}'", id)));
+        }
+        Self::read(&path, id)
+    }
+
+    /// Every valid theme in the themes directory, sorted by id. Files that
+    /// don't load are skipped and logged.
+    pub fn list() -> Vec<Self> {
+        let Some(dir) = Self::dir() else {
+            return Vec::new();
+        };
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut themes: Vec<Self> = read_dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(THEME_EXTENSION))
+            .filter_map(|path| {
+                let id = path.file_stem()?.to_str()?.to_string();
+                if THEME_PRESETS.contains(&id.as_str()) {
+                    eprintln!("Skipping theme {}: it has the name of a built-in theme", path.display());
+                    return None;
+                }
+                Self::read(&path, &id)
+                    .map_err(|e| eprintln!("Skipping theme {}: {}", path.display(), e))
+                    .ok()
+            })
+            .collect();
+        themes.sort_by(|a, b| a.id.cmp(&b.id));
+        themes
+    }
+
+    /// Write the theme to the themes directory, replacing one with the same id
+    pub fn save(&self) -> Result<PathBuf> {
+        if THEME_PRESETS.contains(&self.id.as_str()) {
+            return Err(KiroError::validation("id", format!("'{}' is a built-in theme", self.id)));
+        }
+        self.validate()?;
+        let path = Self::path(&self.id)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        self.write(&path)?;
+        Ok(path)
+    }
+
+    /// Write the theme to any file, e.g. to share it
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Copy a theme file into the themes directory. Its id comes from the
+    /// file name.
+    pub fn import(path: &Path) -> Result<Self> {
+        let id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(slugify)
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| KiroError::InvalidPath(path.display().to_string()))?;
+        let theme = Self::read(path, &id)?;
+        theme.save()?;
+        Ok(theme)
+    }
+
+    pub fn delete(id: &str) -> Result<()> {
+        let path = Self::path(id)?;
+        if !path.is_file() {
+            return Err(KiroError::validation("id", format!("no theme named '{}'", id)));
+        }
+        fs::remove_file(path)?;
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
+use std::path::PathBuf;
+use std::sync::PoisonError;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,17 +12,147 @@ pub enum KiroError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
     #[error("Note not found: {0}")]
     NoteNotFound(i64),
 
     #[error("Invalid path: {0}")]
     InvalidPath(String),
 
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+
+    #[error("Invalid template: {0}")]
+    InvalidTemplate(String),
+
+    #[error("Invalid backup: {0}")]
+    InvalidBackup(String),
+
+    #[error("Database schema version {0} is newer than this version of Kiro supports")]
+    UnsupportedSchema(i32),
+
+    #[error("Migration failed: {0}")]
+    MigrationFailed(String),
+
+    #[error("Notes are locked")]
+    Locked,
+
+    #[error("Note {0} is locked")]
+    NoteLocked(i64),
+
+    #[error("Incorrect passphrase")]
+    WrongPassphrase,
+
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
     #[error("Import failed: {0}")]
     ImportFailed(String),
 
     #[error("Export failed: {0}")]
     ExportFailed(String),
+
+    /// A thread panicked while holding shared state
+    #[error("Internal state is unavailable after an earlier failure: {0}")]
+    LockPoisoned(String),
+
+    #[error("Invalid {field}: {message}")]
+    Validation { field: String, message: String },
+
+    #[error("Could not parse {}: {message} at line {line}, column {column}", path.display())]
+    ConfigParse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error("Missing or invalid API token")]
+    Unauthorized,
+
+    #[error("{0} is not available")]
+    Unavailable(String),
+
+    #[error("Window error: {0}")]
+    Window(String),
+}
+
+impl KiroError {
+    pub fn validation(field: impl Into<String>, message: impl Into<String>) -> Self {
+        KiroError::Validation {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Stable identifier for the frontend and API clients to match on
+    pub fn code(&self) -> &'static str {
+        match self {
+            KiroError::Database(_) => "database",
+            KiroError::Io(_) => "io",
+            KiroError::Json(_) => "json",
+            KiroError::NoteNotFound(_) => "note_not_found",
+            KiroError::InvalidPath(_) => "invalid_path",
+            KiroError::InvalidPattern(_) => "invalid_pattern",
+            KiroError::InvalidTemplate(_) => "invalid_template",
+            KiroError::InvalidBackup(_) => "invalid_backup",
+            KiroError::UnsupportedSchema(_) => "unsupported_schema",
+            KiroError::MigrationFailed(_) => "migration_failed",
+            KiroError::Locked => "locked",
+            KiroError::NoteLocked(_) => "note_locked",
+            KiroError::WrongPassphrase => "wrong_passphrase",
+            KiroError::Encryption(_) => "encryption",
+            KiroError::ImportFailed(_) => "import_failed",
+            KiroError::ExportFailed(_) => "export_failed",
+            KiroError::LockPoisoned(_) => "lock_poisoned",
+            KiroError::Validation { .. } => "validation",
+            KiroError::ConfigParse { .. } => "config_parse",
+            KiroError::Unauthorized => "unauthorized",
+            KiroError::Unavailable(_) => "unavailable",
+            KiroError::Window(_) => "window",
+        }
+    }
+}
+
+impl<T> From<PoisonError<T>> for KiroError {
+    fn from(e: PoisonError<T>) -> Self {
+        KiroError::LockPoisoned(e.to_string())
+    }
+}
+
+/// Serializes as `{ "code": ..., "message": ... }` plus the variant's
+/// fields, e.g. `id` for `note_not_found`
+impl Serialize for KiroError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("code", self.code())?;
+        map.serialize_entry("message", &self.to_string())?;
+
+        match self {
+            KiroError::NoteNotFound(id) | KiroError::NoteLocked(id) => {
+                map.serialize_entry("id", id)?;
+            }
+            KiroError::InvalidPath(path) => {
+                map.serialize_entry("path", path)?;
+            }
+            KiroError::UnsupportedSchema(version) => {
+                map.serialize_entry("version", version)?;
+            }
+            KiroError::Validation { field, .. } => {
+                map.serialize_entry("field", field)?;
+            }
+            KiroError::ConfigParse { path, line, column, .. } => {
+                map.serialize_entry("path", path)?;
+                map.serialize_entry("line", line)?;
+                map.serialize_entry("column", column)?;
+            }
+            _ => {}
+        }
+
+        map.end()
+    }
 }
 
 pub type Result<T> = std::result::Result<T, KiroError>;
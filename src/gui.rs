@@ -0,0 +1,370 @@
+//! Tauri application setup and its background jobs
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, Utc};
+use notify::{RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter, Manager, Theme, WindowEvent};
+
+use crate::commands::{self, AppState};
+use crate::config::{Config, ConfigDiagnostic};
+use crate::server;
+use crate::store::{self, NoteStore, StoreEvent};
+
+/// How often the backup scheduler checks whether a backup is due
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// How often the auto-lock timer checks for inactivity
+const AUTO_LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the theme schedule is checked
+const THEME_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long to let an editor finish writing config.json before reloading it
+const CONFIG_RELOAD_DELAY: Duration = Duration::from_millis(250);
+
+/// Take an automatic backup whenever the newest one is older than the
+/// configured interval
+fn spawn_backup_scheduler(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let state = app.state::<AppState>();
+        let settings = match state.config.lock() {
+            Ok(config) => config.backup.clone(),
+            Err(_) => return,
+        };
+
+        if settings.enabled {
+            if let Ok(store) = state.store.lock() {
+                let dir = settings.dir_for(store.path());
+                let due = store::backup::last_backup_time(&dir).is_none_or(|last| {
+                    Utc::now() - last >= chrono::Duration::hours(settings.interval_hours.into())
+                });
+
+                if due {
+                    if let Err(e) = store.auto_backup(&dir, settings.keep) {
+                        eprintln!("Automatic backup failed: {}", e);
+                    }
+                }
+            }
+        }
+
+        std::thread::sleep(BACKUP_CHECK_INTERVAL);
+    });
+}
+
+/// Lock encrypted and secret notes after the configured idle time and
+/// tell the frontend with a `notes-locked` event
+fn spawn_auto_lock(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(AUTO_LOCK_CHECK_INTERVAL);
+
+        let state = app.state::<AppState>();
+        let minutes = match state.config.lock() {
+            Ok(config) => config.security.auto_lock_minutes,
+            Err(_) => return,
+        };
+        let idle = match state.last_activity.lock() {
+            Ok(last) => last.elapsed(),
+            Err(_) => return,
+        };
+
+        if minutes == 0 || idle < Duration::from_secs(u64::from(minutes) * 60) {
+            continue;
+        }
+
+        let Ok(store) = state.store.lock() else {
+            return;
+        };
+        let unlocked = store.is_encrypted() && !store.is_locked();
+        if unlocked || store.has_unlocked_secrets() {
+            store.lock_notes();
+            let _ = app.emit("notes-locked", ());
+        }
+    });
+}
+
+/// Emit `theme-changed` with the preset to show whenever the theme mode
+/// picks a different one than was last sent
+pub(crate) fn update_active_theme(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let Ok(preset) = commands::active_theme(&state) else {
+        return;
+    };
+    let Ok(mut shown) = state.shown_theme.lock() else {
+        return;
+    };
+    if *shown != preset {
+        *shown = preset.clone();
+        let _ = app.emit("theme-changed", preset);
+    }
+}
+
+/// Record the desktop's color scheme, as reported by the window
+fn set_system_theme(app: &AppHandle, theme: Theme) {
+    if let Ok(mut system_dark) = app.state::<AppState>().system_dark.lock() {
+        *system_dark = Some(theme == Theme::Dark);
+    }
+    update_active_theme(app);
+}
+
+/// Switch presets when the "schedule" theme mode reaches a start time
+fn spawn_theme_scheduler(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(THEME_CHECK_INTERVAL);
+        update_active_theme(&app);
+    });
+}
+
+/// Reload config.json when it is changed outside the app, e.g. by hand or
+/// by a dotfile manager. A symlinked file is followed to its target.
+fn spawn_config_watcher(app: AppHandle) {
+    let Some(path) = Config::config_path() else {
+        return;
+    };
+    let mut watched = vec![path.clone()];
+    if let Ok(target) = std::fs::canonicalize(&path) {
+        if target != path {
+            watched.push(target);
+        }
+    }
+
+    let (sender, events) = mpsc::channel();
+    let mut watcher = match notify::recommended_watcher(sender) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("Config watcher unavailable: {}", e);
+            return;
+        }
+    };
+    // Saves rename a new file into place, so watch the directory rather
+    // than the file
+    for dir in watched.iter().filter_map(|p| p.parent()) {
+        if let Err(e) = std::fs::create_dir_all(dir)
+            .map_err(notify::Error::io)
+            .and_then(|_| watcher.watch(dir, RecursiveMode::NonRecursive))
+        {
+            eprintln!("Could not watch {}: {}", dir.display(), e);
+        }
+    }
+
+    std::thread::spawn(move || {
+        let _watcher = watcher;
+        // Reading the file reports access events; only writes matter
+        let touches_config = |event: &notify::Result<notify::Event>| {
+            event.as_ref().is_ok_and(|event| {
+                !event.kind.is_access() && event.paths.iter().any(|p| watched.contains(p))
+            })
+        };
+
+        while let Ok(event) = events.recv() {
+            if !touches_config(&event) {
+                continue;
+            }
+            std::thread::sleep(CONFIG_RELOAD_DELAY);
+            let _ = events.try_iter().count();
+            reload_config(&app, &path);
+        }
+    });
+}
+
+/// Replace `AppState.config` with the file on disk and emit
+/// `config-changed`. A file that doesn't parse or has invalid values is
+/// reported with `config-invalid` and the current settings stay in use, so
+/// a half-finished edit never resets anything.
+fn reload_config(app: &AppHandle, path: &Path) {
+    let state = app.state::<AppState>();
+    let config = match Config::read(path) {
+        Ok((config, _)) => {
+            let diagnostics = config.problems();
+            if !diagnostics.is_empty() {
+                report_config_problems(app, diagnostics);
+                return;
+            }
+            config
+        }
+        Err(e) => {
+            let diagnostic = ConfigDiagnostic::from_error(&e, "keeping the current settings");
+            report_config_problems(app, vec![diagnostic]);
+            return;
+        }
+    };
+
+    {
+        let Ok(mut current) = state.config.lock() else {
+            return;
+        };
+        // Our own saves land here too
+        if *current == config {
+            return;
+        }
+        *current = config.clone();
+    }
+    if let Ok(mut store) = state.store.lock() {
+        store.set_backup_settings(&config.backup);
+    }
+
+    report_config_problems(app, Vec::new());
+    let _ = app.emit("config-changed", config);
+    update_active_theme(app);
+}
+
+fn report_config_problems(app: &AppHandle, diagnostics: Vec<ConfigDiagnostic>) {
+    let state = app.state::<AppState>();
+    if let Ok(mut current) = state.config_diagnostics.lock() {
+        current.clone_from(&diagnostics);
+    }
+    if !diagnostics.is_empty() {
+        let _ = app.emit("config-invalid", diagnostics);
+    }
+}
+
+/// Forward note store changes to every window as `store-changed` events,
+/// so views stay current whichever command or job made the change. Each
+/// vault's store gets its own forwarder.
+pub(crate) fn spawn_event_forwarder(app: AppHandle, events: Receiver<StoreEvent>) {
+    std::thread::spawn(move || {
+        for event in events {
+            let _ = app.emit("store-changed", event);
+        }
+    });
+}
+
+/// Database given with `--db <path>` or `--db=<path>` on the command line
+fn db_arg() -> Option<PathBuf> {
+    let mut args = std::env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--db" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.to_str().and_then(|arg| arg.strip_prefix("--db=")) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    let (config, config_diagnostics) = Config::load();
+    for diagnostic in &config_diagnostics {
+        eprintln!("config.json: {}", diagnostic.message);
+    }
+
+    // Ensure database directory exists
+    let db_path = crate::db_path(db_arg(), &config);
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent).expect("Failed to create database directory");
+    }
+
+    // Open database
+    let store = NoteStore::open(&db_path, &config.backup).expect("Failed to open database");
+    let events = store.subscribe();
+    let shown_theme = config.theme.active_preset(None, Local::now().time()).to_string();
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_shell::init())
+        .manage(AppState {
+            store: Mutex::new(store),
+            config: Mutex::new(config),
+            config_diagnostics: Mutex::new(config_diagnostics),
+            last_activity: Mutex::new(Instant::now()),
+            system_dark: Mutex::new(None),
+            shown_theme: Mutex::new(shown_theme),
+        })
+        .on_window_event(|window, event| {
+            if let WindowEvent::ThemeChanged(theme) = event {
+                set_system_theme(window.app_handle(), *theme);
+            }
+        })
+        .setup(|app| {
+            spawn_event_forwarder(app.handle().clone(), events);
+            spawn_backup_scheduler(app.handle().clone());
+            spawn_auto_lock(app.handle().clone());
+            server::spawn(app.handle().clone());
+            spawn_config_watcher(app.handle().clone());
+            if let Some(theme) = app.get_webview_window("main").and_then(|w| w.theme().ok()) {
+                set_system_theme(app.handle(), theme);
+            }
+            spawn_theme_scheduler(app.handle().clone());
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            commands::search,
+            commands::get_note,
+            commands::create_note,
+            commands::update_note,
+            commands::update_note_full,
+            commands::delete_notes,
+            commands::get_note_count,
+            commands::seed_notes,
+            commands::list_note_templates,
+            commands::get_note_template,
+            commands::save_note_template,
+            commands::delete_note_template,
+            commands::create_note_from_template,
+            commands::open_daily_note,
+            commands::adjacent_daily_note,
+            commands::get_journal_settings,
+            commands::save_journal_settings,
+            commands::get_home_directories,
+            commands::scan_directories,
+            commands::import_files,
+            commands::export_notes,
+            commands::list_export_templates,
+            commands::backup_database,
+            commands::restore_database,
+            commands::list_backups,
+            commands::get_backup_settings,
+            commands::save_backup_settings,
+            commands::get_lock_state,
+            commands::unlock_notes,
+            commands::lock_notes,
+            commands::set_note_secret,
+            commands::clear_note_secret,
+            commands::unlock_note,
+            commands::lock_note,
+            commands::enable_encryption,
+            commands::disable_encryption,
+            commands::get_security_settings,
+            commands::save_security_settings,
+            commands::get_api_settings,
+            commands::save_api_settings,
+            commands::get_config,
+            commands::get_config_diagnostics,
+            commands::list_vaults,
+            commands::create_vault,
+            commands::open_vault,
+            commands::switch_vault,
+            commands::close_vault,
+            commands::get_keybindings,
+            commands::set_keybinding,
+            commands::reset_keybindings,
+            commands::list_keymap_profiles,
+            commands::export_keymap_profile,
+            commands::import_keymap_profile,
+            commands::get_scan_directories,
+            commands::set_scan_directories,
+            commands::get_scan_settings,
+            commands::save_scan_settings,
+            commands::get_theme_settings,
+            commands::set_theme_preset,
+            commands::set_custom_colors,
+            commands::set_font_settings,
+            commands::save_theme_settings,
+            commands::get_active_theme,
+            commands::list_themes,
+            commands::import_theme,
+            commands::export_theme,
+            commands::create_theme,
+            commands::delete_theme,
+            commands::window_start_drag,
+            commands::window_minimize,
+            commands::window_maximize,
+            commands::window_close,
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
@@ -1,65 +1,32 @@
+#[cfg(feature = "gui")]
 pub mod commands;
 pub mod config;
 pub mod error;
+#[cfg(feature = "gui")]
+mod gui;
+pub mod scan;
+#[cfg(feature = "gui")]
+mod server;
 pub mod store;
+pub mod template;
 
-use std::sync::Mutex;
+#[cfg(feature = "gui")]
+pub use gui::run;
 
-use commands::AppState;
-use config::Config;
-use store::NoteStore;
+use std::path::PathBuf;
 
-fn get_db_path() -> std::path::PathBuf {
+/// Location of the default vault's database
+pub fn default_db_path() -> PathBuf {
     dirs::data_local_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .unwrap_or_else(|| PathBuf::from("."))
         .join("kiro")
         .join("notes.db")
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    // Ensure database directory exists
-    let db_path = get_db_path();
-    if let Some(parent) = db_path.parent() {
-        std::fs::create_dir_all(parent).expect("Failed to create database directory");
-    }
-
-    // Open database
-    let store = NoteStore::open(&db_path).expect("Failed to open database");
-    let config = Config::load();
-
-    tauri::Builder::default()
-        .plugin(tauri_plugin_shell::init())
-        .manage(AppState {
-            store: Mutex::new(store),
-            config: Mutex::new(config),
-        })
-        .invoke_handler(tauri::generate_handler![
-            commands::search,
-            commands::get_note,
-            commands::create_note,
-            commands::update_note,
-            commands::update_note_full,
-            commands::delete_notes,
-            commands::get_note_count,
-            commands::seed_notes,
-            commands::get_home_directories,
-            commands::scan_directories,
-            commands::import_files,
-            commands::export_notes,
-            commands::get_config,
-            commands::get_scan_directories,
-            commands::set_scan_directories,
-            commands::get_theme_settings,
-            commands::set_theme_preset,
-            commands::set_custom_colors,
-            commands::set_font_settings,
-            commands::save_theme_settings,
-            commands::window_start_drag,
-            commands::window_minimize,
-            commands::window_maximize,
-            commands::window_close,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+/// Database to open, shared by the GUI and the `kiro` CLI: `explicit`
+/// (a `--db` flag) if given, then `KIRO_DB`, then the config's current vault
+pub fn db_path(explicit: Option<PathBuf>, config: &config::Config) -> PathBuf {
+    explicit
+        .or_else(|| std::env::var_os("KIRO_DB").filter(|path| !path.is_empty()).map(PathBuf::from))
+        .unwrap_or_else(|| config.current_vault_path())
 }
@@ -0,0 +1,279 @@
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::error::{KiroError, Result};
+
+/// A compiled glob pattern.
+///
+/// Supports `*` and `?` within a path segment, `**` across segments,
+/// character classes (`[abc]`, `[a-z]`, `[!abc]`), brace alternation
+/// (`{md,txt}`, nestable) and `\` escapes. Every other character is
+/// matched literally.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    regex: Regex,
+    has_path: bool,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim_start_matches('/');
+        let regex = Regex::new(&format!("^{}$", translate(pattern)?))
+            .map_err(|e| KiroError::InvalidPattern(format!("{}: {}", pattern, e)))?;
+
+        Ok(Self {
+            regex,
+            has_path: pattern.contains('/'),
+        })
+    }
+
+    /// True if the pattern contains a `/` and so must match a whole
+    /// relative path rather than just a file name
+    pub fn has_path(&self) -> bool {
+        self.has_path
+    }
+
+    /// Match a string against the whole pattern
+    pub fn is_match_str(&self, s: &str) -> bool {
+        self.regex.is_match(s)
+    }
+
+    /// Match a relative path. Patterns without a `/` only look at the
+    /// final component.
+    pub fn is_match(&self, relative: &Path) -> bool {
+        if self.has_path {
+            self.is_match_str(&relative.to_string_lossy().replace('\\', "/"))
+        } else {
+            relative
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|name| self.is_match_str(name))
+        }
+    }
+}
+
+/// A list of globs. A path matches if it matches any positive pattern
+/// and none of the `!`-prefixed ones. A list with only negations
+/// matches everything they don't exclude.
+#[derive(Debug, Clone, Default)]
+pub struct GlobSet {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+impl GlobSet {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self> {
+        let mut set = Self::default();
+        for pattern in patterns {
+            let pattern = pattern.as_ref().trim();
+            if pattern.is_empty() {
+                continue;
+            }
+            match pattern.strip_prefix('!') {
+                Some(negated) => set.exclude.push(Glob::new(negated)?),
+                None => set.include.push(Glob::new(pattern)?),
+            }
+        }
+        Ok(set)
+    }
+
+    pub fn is_match(&self, relative: &Path) -> bool {
+        let included = self.include.is_empty() || self.include.iter().any(|g| g.is_match(relative));
+        included && !self.exclude.iter().any(|g| g.is_match(relative))
+    }
+}
+
+/// Translate a glob into the body of a regex (without anchors)
+fn translate(pattern: &str) -> Result<String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut regex = String::new();
+    let mut brace_depth = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                i += 1;
+                let escaped = chars.get(i).ok_or_else(|| {
+                    KiroError::InvalidPattern(format!("{}: trailing backslash", pattern))
+                })?;
+                regex.push_str(&regex::escape(&escaped.to_string()));
+            }
+            '*' => {
+                let start = i;
+                while chars.get(i + 1) == Some(&'*') {
+                    i += 1;
+                }
+                let double = i > start;
+                let segment_start = start == 0 || chars[start - 1] == '/';
+
+                if double && segment_start && chars.get(i + 1) == Some(&'/') {
+                    // `**/` matches zero or more whole directories
+                    regex.push_str("(?:.*/)?");
+                    i += 1;
+                } else if double && segment_start && i + 1 == chars.len() {
+                    // Trailing `**` matches everything below
+                    regex.push_str(".*");
+                } else {
+                    regex.push_str("[^/]*");
+                }
+            }
+            '?' => regex.push_str("[^/]"),
+            '[' => match parse_class(&chars, i) {
+                Some((class, end)) => {
+                    regex.push_str(&class);
+                    i = end;
+                }
+                None => regex.push_str("\\["),
+            },
+            '{' => {
+                brace_depth += 1;
+                regex.push_str("(?:");
+            }
+            ',' if brace_depth > 0 => regex.push('|'),
+            '}' if brace_depth > 0 => {
+                brace_depth -= 1;
+                regex.push(')');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+
+    if brace_depth > 0 {
+        return Err(KiroError::InvalidPattern(format!("{}: unclosed '{{'", pattern)));
+    }
+
+    Ok(regex)
+}
+
+/// Parse a character class starting at `chars[start] == '['`. Returns the
+/// regex class and the index of the closing `]`, or None if unterminated.
+/// Like `*` and `?`, a class never matches `/`.
+fn parse_class(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut i = start + 1;
+    let negated = matches!(chars.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut items = String::new();
+    let mut first = true;
+
+    loop {
+        let c = *chars.get(i)?;
+        match c {
+            // A `]` right after the opening bracket is a literal
+            ']' if !first => break,
+            '\\' => {
+                i += 1;
+                let escaped = *chars.get(i)?;
+                items.push_str(&regex::escape(&escaped.to_string()));
+            }
+            // `-` at either end of the class is a literal, not a range
+            '-' if first || chars.get(i + 1) == Some(&']') => items.push_str("\\-"),
+            '[' | ']' | '^' | '&' | '~' => {
+                items.push('\\');
+                items.push(c);
+            }
+            c => items.push(c),
+        }
+        first = false;
+        i += 1;
+    }
+
+    let class = if negated {
+        format!("[^{}/]", items)
+    } else {
+        format!("[{}&&[^/]]", items)
+    };
+    Some((class, i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, s: &str) -> bool {
+        Glob::new(pattern).unwrap().is_match_str(s)
+    }
+
+    #[test]
+    fn star_stays_within_a_segment() {
+        assert!(matches("*.md", "notes.md"));
+        assert!(!matches("*.md", "dir/notes.md"));
+        assert!(matches("a?c", "abc"));
+        assert!(!matches("a?c", "a/c"));
+    }
+
+    #[test]
+    fn double_star_crosses_segments() {
+        assert!(matches("**/*.md", "notes.md"));
+        assert!(matches("**/*.md", "a/b/notes.md"));
+        assert!(matches("docs/**", "docs/a/b.txt"));
+        assert!(!matches("docs/**", "other/a.txt"));
+        assert!(matches("a/**/b", "a/b"));
+        assert!(matches("a/**/b", "a/x/y/b"));
+        // Not a whole segment, so it's an ordinary star
+        assert!(!matches("a**/b", "ax/y/b"));
+    }
+
+    #[test]
+    fn classes() {
+        assert!(matches("[abc].txt", "b.txt"));
+        assert!(!matches("[abc].txt", "d.txt"));
+        assert!(matches("[a-c].txt", "b.txt"));
+        assert!(matches("[]x]", "]"));
+        assert!(matches("[a-]", "-"));
+        assert!(!matches("[a-]", "b"));
+        assert!(matches("[\\]]", "]"));
+        assert!(Glob::new("[unclosed").unwrap().is_match_str("[unclosed"));
+    }
+
+    #[test]
+    fn negated_classes() {
+        assert!(matches("[!abc]", "d"));
+        assert!(!matches("[!abc]", "a"));
+        assert!(matches("[^abc]", "d"));
+        // A leading `-` is literal, not a range from `/`
+        assert!(!matches("[!-a]", "-"));
+        assert!(!matches("[!-a]", "a"));
+        assert!(matches("[!-a]", "5"));
+    }
+
+    #[test]
+    fn classes_never_match_slash() {
+        assert!(!matches("a[!x]b", "a/b"));
+        assert!(!matches("a[.-0]b", "a/b"));
+        assert!(matches("a[.-0]b", "a.b"));
+    }
+
+    #[test]
+    fn braces_and_escapes() {
+        assert!(matches("*.{md,txt}", "a.txt"));
+        assert!(matches("{a,{b,c}}", "c"));
+        assert!(!matches("*.{md,txt}", "a.rs"));
+        assert!(matches("\\*", "*"));
+        assert!(!matches("\\*", "a"));
+        assert!(Glob::new("{a,b").is_err());
+    }
+
+    #[test]
+    fn paths_and_negation() {
+        let name = Glob::new("*.md").unwrap();
+        assert!(name.is_match(Path::new("a/b/notes.md")));
+        let path = Glob::new("/docs/*.md").unwrap();
+        assert!(path.is_match(Path::new("docs/notes.md")));
+        assert!(!path.is_match(Path::new("other/docs/notes.md")));
+
+        let set = GlobSet::new(&["*.md", "!draft*"]).unwrap();
+        assert!(set.is_match(Path::new("notes.md")));
+        assert!(!set.is_match(Path::new("draft.md")));
+        assert!(!set.is_match(Path::new("notes.txt")));
+
+        let only_negated = GlobSet::new(&["!*.tmp"]).unwrap();
+        assert!(only_negated.is_match(Path::new("a.md")));
+        assert!(!only_negated.is_match(Path::new("a.tmp")));
+    }
+}
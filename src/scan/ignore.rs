@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::glob::Glob;
+
+/// Ignore files read from every scanned directory
+pub const IGNORE_FILES: &[&str] = &[".gitignore", ".kiroignore"];
+
+/// A single gitignore-style rule, scoped to the directory it was declared in
+#[derive(Debug, Clone)]
+pub struct IgnoreRule {
+    base: PathBuf,
+    glob: Glob,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    /// Parse one line of an ignore file. Returns None for blanks, comments
+    /// and patterns that can't be compiled.
+    pub fn parse(line: &str, base: &Path) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+
+        // A slash anywhere but the end ties the pattern to `base`
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+        if pattern.is_empty() {
+            return None;
+        }
+
+        let glob = Glob::new(pattern).ok()?;
+        Some(Self {
+            base: base.to_path_buf(),
+            glob,
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+
+        if self.anchored {
+            self.glob.is_match_str(&relative.to_string_lossy().replace('\\', "/"))
+        } else {
+            self.glob.is_match(relative)
+        }
+    }
+}
+
+/// Ordered set of ignore rules; later rules override earlier ones
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// Build rules from a list of patterns, all relative to `base`
+    pub fn from_patterns<S: AsRef<str>>(patterns: &[S], base: &Path) -> Self {
+        Self {
+            rules: patterns
+                .iter()
+                .filter_map(|p| IgnoreRule::parse(p.as_ref(), base))
+                .collect(),
+        }
+    }
+
+    /// Return a copy of these rules extended with any ignore files in `dir`
+    pub fn with_ignore_files(&self, dir: &Path) -> Self {
+        let mut rules = self.clone();
+        for name in IGNORE_FILES {
+            if let Ok(contents) = fs::read_to_string(dir.join(name)) {
+                rules
+                    .rules
+                    .extend(contents.lines().filter_map(|l| IgnoreRule::parse(l, dir)));
+            }
+        }
+        rules
+    }
+
+    /// Check whether a path is ignored. The last matching rule decides.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(path, is_dir))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
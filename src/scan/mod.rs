@@ -0,0 +1,243 @@
+pub mod glob;
+pub mod ignore;
+pub mod preview;
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ScanSettings;
+use glob::GlobSet;
+use ignore::IgnoreRules;
+use preview::Encoding;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+    /// Detected text encoding, set when a preview was requested
+    pub encoding: Option<Encoding>,
+    /// First non-empty lines of the file, set when a preview was requested
+    #[serde(default)]
+    pub preview: Vec<String>,
+    /// Whether the content is already in the store, set when requested
+    pub exists_in_store: Option<bool>,
+}
+
+/// Per-scan options chosen in the import dialog
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// Number of preview lines to read from each file (0 = no preview)
+    pub preview_lines: usize,
+    /// Check each file against existing notes
+    pub check_existing: bool,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub modified_after: Option<DateTime<Utc>>,
+    pub modified_before: Option<DateTime<Utc>>,
+}
+
+impl ScanOptions {
+    /// Check a file's size and modified time against the filters
+    fn accepts(&self, size: u64, modified: Option<DateTime<Utc>>) -> bool {
+        if self.min_size.is_some_and(|min| size < min) || self.max_size.is_some_and(|max| size > max) {
+            return false;
+        }
+
+        match modified {
+            Some(modified) => {
+                self.modified_after.is_none_or(|after| modified >= after)
+                    && self.modified_before.is_none_or(|before| modified <= before)
+            }
+            // Without a modified time, only pass if no date range was asked for
+            None => self.modified_after.is_none() && self.modified_before.is_none(),
+        }
+    }
+}
+
+/// Why a file or directory was left out of the scan results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Matched an exclude pattern from the config
+    Excluded,
+    /// Matched a .gitignore or .kiroignore rule
+    Ignored,
+    /// Directory is deeper than the configured max depth
+    MaxDepth,
+    /// File is larger than the configured max file size
+    TooLarge,
+    /// Symlink points back to a directory that was already scanned
+    SymlinkLoop,
+    /// Directory or file metadata could not be read
+    Unreadable,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkippedEntry {
+    pub path: PathBuf,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScanResult {
+    pub files: Vec<FileEntry>,
+    pub skipped: Vec<SkippedEntry>,
+    /// True if scanning stopped early because `max_results` was reached
+    pub truncated: bool,
+}
+
+/// Recursive directory walker used by the import dialog
+pub struct Scanner<'a> {
+    settings: &'a ScanSettings,
+    patterns: &'a GlobSet,
+    options: &'a ScanOptions,
+    visited: HashSet<PathBuf>,
+    result: ScanResult,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(settings: &'a ScanSettings, patterns: &'a GlobSet, options: &'a ScanOptions) -> Self {
+        Self {
+            settings,
+            patterns,
+            options,
+            visited: HashSet::new(),
+            result: ScanResult::default(),
+        }
+    }
+
+    /// Scan each root directory and return the collected results
+    pub fn scan(mut self, roots: &[PathBuf]) -> ScanResult {
+        for root in roots {
+            if self.result.truncated {
+                break;
+            }
+
+            let excludes = IgnoreRules::from_patterns(&self.settings.exclude_patterns, root);
+            self.scan_dir(root, root, 0, &excludes, &IgnoreRules::default());
+        }
+
+        if self.options.preview_lines > 0 {
+            for file in &mut self.result.files {
+                if let Some((encoding, lines)) = preview::sample_file(&file.path, self.options.preview_lines) {
+                    file.encoding = Some(encoding);
+                    file.preview = lines;
+                }
+            }
+        }
+
+        self.result.files.sort_by_key(|f| f.name.to_lowercase());
+        self.result
+    }
+
+    fn skip(&mut self, path: &Path, reason: SkipReason) {
+        self.result.skipped.push(SkippedEntry {
+            path: path.to_path_buf(),
+            reason,
+        });
+    }
+
+    /// Match a file against the scan patterns, relative to its scan root
+    fn is_match(&self, root: &Path, path: &Path) -> bool {
+        path.strip_prefix(root)
+            .is_ok_and(|relative| self.patterns.is_match(relative))
+    }
+
+    fn scan_dir(
+        &mut self,
+        root: &Path,
+        dir: &Path,
+        depth: usize,
+        excludes: &IgnoreRules,
+        ignores: &IgnoreRules,
+    ) {
+        // Canonicalize so symlinked directories are only walked once
+        let Ok(canonical) = fs::canonicalize(dir) else {
+            self.skip(dir, SkipReason::Unreadable);
+            return;
+        };
+        if !self.visited.insert(canonical) {
+            if fs::symlink_metadata(dir).is_ok_and(|m| m.file_type().is_symlink()) {
+                self.skip(dir, SkipReason::SymlinkLoop);
+            }
+            return;
+        }
+
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            self.skip(dir, SkipReason::Unreadable);
+            return;
+        };
+
+        let ignores = if self.settings.respect_ignore_files {
+            ignores.with_ignore_files(dir)
+        } else {
+            ignores.clone()
+        };
+
+        for entry in read_dir.filter_map(|e| e.ok()) {
+            if self.result.truncated {
+                return;
+            }
+
+            let path = entry.path();
+            let name = path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_string();
+
+            // Follows symlinks, so a link to a directory is walked as one
+            let Ok(metadata) = fs::metadata(&path) else {
+                self.skip(&path, SkipReason::Unreadable);
+                continue;
+            };
+
+            if metadata.is_dir() {
+                // Skip hidden directories
+                if name.starts_with('.') {
+                    continue;
+                }
+                if excludes.is_ignored(&path, true) {
+                    self.skip(&path, SkipReason::Excluded);
+                } else if ignores.is_ignored(&path, true) {
+                    self.skip(&path, SkipReason::Ignored);
+                } else if depth >= self.settings.max_depth {
+                    self.skip(&path, SkipReason::MaxDepth);
+                } else {
+                    self.scan_dir(root, &path, depth + 1, excludes, &ignores);
+                }
+            } else if metadata.is_file() && self.is_match(root, &path) {
+                let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+                if !self.options.accepts(metadata.len(), modified) {
+                    continue;
+                }
+
+                if excludes.is_ignored(&path, false) {
+                    self.skip(&path, SkipReason::Excluded);
+                } else if ignores.is_ignored(&path, false) {
+                    self.skip(&path, SkipReason::Ignored);
+                } else if metadata.len() > self.settings.max_file_size {
+                    self.skip(&path, SkipReason::TooLarge);
+                } else if self.result.files.len() >= self.settings.max_results {
+                    self.result.truncated = true;
+                } else {
+                    self.result.files.push(FileEntry {
+                        path,
+                        name,
+                        size: metadata.len(),
+                        modified,
+                        encoding: None,
+                        preview: Vec::new(),
+                        exists_in_store: None,
+                    });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Bytes read from the start of a file for encoding detection and preview
+const SAMPLE_SIZE: usize = 8 * 1024;
+
+/// Longest preview line, in characters
+const MAX_LINE_LEN: usize = 200;
+
+/// Text encoding guessed from the first bytes of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Encoding {
+    #[serde(rename = "ascii")]
+    Ascii,
+    #[serde(rename = "utf-8")]
+    Utf8,
+    #[serde(rename = "utf-8-bom")]
+    Utf8Bom,
+    #[serde(rename = "utf-16le")]
+    Utf16Le,
+    #[serde(rename = "utf-16be")]
+    Utf16Be,
+    /// Not valid UTF-8, probably a legacy 8-bit encoding
+    #[serde(rename = "unknown")]
+    Unknown,
+    /// Contains NUL bytes, not a text file
+    #[serde(rename = "binary")]
+    Binary,
+}
+
+impl Encoding {
+    /// Whether `NoteStore::import_file` can read this file as text
+    pub fn is_importable(&self) -> bool {
+        matches!(self, Encoding::Ascii | Encoding::Utf8 | Encoding::Utf8Bom)
+    }
+}
+
+/// Detect the encoding of a byte sample
+pub fn detect_encoding(bytes: &[u8]) -> Encoding {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return Encoding::Utf8Bom;
+    }
+    if bytes.starts_with(&[0xFF, 0xFE]) {
+        return Encoding::Utf16Le;
+    }
+    if bytes.starts_with(&[0xFE, 0xFF]) {
+        return Encoding::Utf16Be;
+    }
+    if bytes.contains(&0) {
+        return Encoding::Binary;
+    }
+    if bytes.is_ascii() {
+        return Encoding::Ascii;
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(_) => Encoding::Utf8,
+        // The sample may have cut a multi-byte character in half
+        Err(e) if e.error_len().is_none() => Encoding::Utf8,
+        Err(_) => Encoding::Unknown,
+    }
+}
+
+/// Read the start of a file and return its encoding and up to `lines`
+/// non-empty lines of text. Binary files get no preview.
+pub fn sample_file(path: &Path, lines: usize) -> Option<(Encoding, Vec<String>)> {
+    let mut bytes = Vec::with_capacity(SAMPLE_SIZE);
+    File::open(path)
+        .ok()?
+        .take(SAMPLE_SIZE as u64)
+        .read_to_end(&mut bytes)
+        .ok()?;
+
+    let encoding = detect_encoding(&bytes);
+    let text = match encoding {
+        Encoding::Binary => return Some((encoding, Vec::new())),
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let units: Vec<u16> = bytes[2..]
+                .chunks_exact(2)
+                .map(|pair| match encoding {
+                    Encoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                    _ => u16::from_be_bytes([pair[0], pair[1]]),
+                })
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        Encoding::Utf8Bom => String::from_utf8_lossy(&bytes[3..]).into_owned(),
+        _ => String::from_utf8_lossy(&bytes).into_owned(),
+    };
+
+    let preview = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .take(lines)
+        .map(|line| line.chars().take(MAX_LINE_LEN).collect())
+        .collect();
+
+    Some((encoding, preview))
+}
@@ -0,0 +1,267 @@
+//! Local HTTP/JSON API mirroring the Tauri commands, for scripts and
+//! editor plugins
+
+use std::io::Read;
+use std::path::PathBuf;
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::commands::{self, AppState};
+use crate::error::KiroError;
+use crate::store::{ExportOptions, TitleStrategy};
+
+/// Largest request body accepted
+const MAX_BODY_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Results returned by `GET /notes` when no `limit` is given
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+
+#[derive(Deserialize)]
+struct NewNote {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    body: String,
+}
+
+#[derive(Deserialize)]
+struct NoteUpdate {
+    title: Option<String>,
+    body: String,
+}
+
+#[derive(Deserialize)]
+struct ImportRequest {
+    paths: Vec<PathBuf>,
+    title_strategy: Option<TitleStrategy>,
+}
+
+#[derive(Deserialize)]
+struct ExportRequest {
+    ids: Vec<i64>,
+    #[serde(default)]
+    options: ExportOptions,
+}
+
+/// HTTP status and error for a failed request
+struct ApiError(u16, KiroError);
+
+impl From<KiroError> for ApiError {
+    fn from(error: KiroError) -> Self {
+        let status = match &error {
+            KiroError::NoteNotFound(_) | KiroError::Unavailable(_) => 404,
+            KiroError::Unauthorized => 401,
+            KiroError::Locked | KiroError::NoteLocked(_) => 423,
+            KiroError::Database(_) | KiroError::Io(_) | KiroError::LockPoisoned(_) => 500,
+            _ => 400,
+        };
+        ApiError(status, error)
+    }
+}
+
+type ApiResult = Result<(u16, Value), ApiError>;
+
+/// Start the API server if it is enabled in the config, generating and
+/// saving a token the first time
+pub fn spawn(app: AppHandle) {
+    let state = app.state::<AppState>();
+    let (port, token) = {
+        let Ok(mut config) = state.config.lock() else {
+            return;
+        };
+        if !config.api.enabled {
+            return;
+        }
+        let token = match &config.api.token {
+            Some(token) if !token.is_empty() => token.clone(),
+            _ => {
+                let token = new_token();
+                config.api.token = Some(token.clone());
+                if let Err(e) = config.save() {
+                    eprintln!("Failed to save HTTP API token: {}", e);
+                }
+                token
+            }
+        };
+        (config.api.port, token)
+    };
+
+    let server = match Server::http(("127.0.0.1", port)) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("HTTP API could not listen on port {}: {}", port, e);
+            return;
+        }
+    };
+
+    std::thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let result = if authorized(&request, &token) {
+                route(&app, &mut request)
+            } else {
+                Err(KiroError::Unauthorized.into())
+            };
+
+            let (status, body) = match result {
+                Ok(reply) => reply,
+                Err(ApiError(status, error)) => (status, json!({ "error": error })),
+            };
+            let content_type = Header::from_bytes("Content-Type", "application/json").expect("valid header");
+            let response = Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(content_type);
+            let _ = request.respond(response);
+        }
+    });
+}
+
+fn route(app: &AppHandle, request: &mut Request) -> ApiResult {
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let state = app.state::<AppState>();
+
+    match (method, segments.as_slice()) {
+        (Method::Get, ["notes"]) => {
+            let q = query_param(query, "q").unwrap_or_default();
+            let limit = match query_param(query, "limit") {
+                Some(limit) => limit
+                    .parse()
+                    .map_err(|_| KiroError::validation("limit", "must be a number"))?,
+                None => DEFAULT_SEARCH_LIMIT,
+            };
+            ok(commands::search(state, &q, limit)?)
+        }
+        (Method::Get, ["notes", id]) => {
+            let id = parse_id(id)?;
+            match commands::get_note(state, id)? {
+                Some(note) => ok(note),
+                None => Err(not_found(id)),
+            }
+        }
+        (Method::Post, ["notes"]) => {
+            let note: NewNote = read_json(request)?;
+            let id = commands::create_note(state, &note.title, &note.body)?;
+            Ok((201, json!({ "id": id })))
+        }
+        (Method::Put, ["notes", id]) => {
+            let id = parse_id(id)?;
+            let update: NoteUpdate = read_json(request)?;
+            if commands::get_note(state.clone(), id)?.is_none() {
+                return Err(not_found(id));
+            }
+            match &update.title {
+                Some(title) => commands::update_note_full(state, id, title, &update.body)?,
+                None => commands::update_note(state, id, &update.body)?,
+            }
+            ok(json!({ "id": id }))
+        }
+        (Method::Delete, ["notes", id]) => {
+            let id = parse_id(id)?;
+            if commands::delete_notes(state, vec![id])? == 0 {
+                return Err(not_found(id));
+            }
+            ok(json!({ "deleted": 1 }))
+        }
+        (Method::Post, ["import"]) => {
+            let import: ImportRequest = read_json(request)?;
+            ok(commands::import_files(state, import.paths, import.title_strategy)?)
+        }
+        (Method::Post, ["export"]) => {
+            let export: ExportRequest = read_json(request)?;
+            let message = commands::export_notes(state, export.ids, export.options)?;
+            ok(json!({ "message": message }))
+        }
+        _ => Err(KiroError::Unavailable(format!("Endpoint {}", path)).into()),
+    }
+}
+
+fn ok<T: Serialize>(value: T) -> ApiResult {
+    serde_json::to_value(value)
+        .map(|value| (200, value))
+        .map_err(|e| ApiError(500, e.into()))
+}
+
+fn not_found(id: i64) -> ApiError {
+    KiroError::NoteNotFound(id).into()
+}
+
+fn parse_id(segment: &str) -> Result<i64, ApiError> {
+    segment
+        .parse()
+        .map_err(|_| KiroError::validation("id", format!("{} is not a number", segment)).into())
+}
+
+fn read_json<T: DeserializeOwned>(request: &mut Request) -> Result<T, ApiError> {
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_SIZE + 1)
+        .read_to_end(&mut body)
+        .map_err(KiroError::from)?;
+    if body.len() as u64 > MAX_BODY_SIZE {
+        return Err(ApiError(413, KiroError::validation("body", "request is too large")));
+    }
+    serde_json::from_slice(&body).map_err(|e| KiroError::validation("body", e.to_string()).into())
+}
+
+fn authorized(request: &Request, token: &str) -> bool {
+    let expected = format!("Bearer {}", token);
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .is_some_and(|h| constant_time_eq(h.value.as_str().as_bytes(), expected.as_bytes()))
+}
+
+/// Compare without returning early, so response timing doesn't reveal how
+/// much of the token matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn new_token() -> String {
+    let mut bytes = [0u8; 24];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Look up a query string parameter, decoding `+` and `%XX` escapes
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('=').or(Some((pair, ""))))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| percent_decode(value))
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
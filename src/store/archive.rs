@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::export::{self, ExportDocument, ExportFormat, NoteRenderer, EXPORT_VERSION};
+use super::note::Note;
+use crate::error::{KiroError, Result};
+
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// Machine-readable copy of every note, used when the archive is imported
+const DATA_NAME: &str = "data/notes.json";
+
+/// Describes the contents of an export archive
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub kiro_export: u32,
+    pub exported_at: DateTime<Utc>,
+    pub format: ExportFormat,
+    pub notes: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub id: i64,
+    pub title: String,
+    /// Path of the rendered note inside the archive
+    pub file: String,
+    /// Content hash used for duplicate detection on import
+    pub hash: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn zip_error(e: zip::result::ZipError) -> KiroError {
+    KiroError::ExportFailed(e.to_string())
+}
+
+fn json_error(e: serde_json::Error) -> KiroError {
+    KiroError::ExportFailed(e.to_string())
+}
+
+/// Write notes to a zip archive at `path`. Each note is rendered under
+/// `notes/`, next to a manifest and a JSON copy for import. Notes have no
+/// tags or attachments in the data model, so the archive holds none.
+pub fn write_archive(
+    path: &Path,
+    document: &ExportDocument,
+    renderer: &NoteRenderer,
+    hash: impl Fn(&Note) -> String,
+) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut entries = Vec::with_capacity(document.notes.len());
+    let mut used = HashSet::new();
+    for note in &document.notes {
+        let name = export::unique_filename(&mut used, renderer.filename(note)?, note.id);
+        let file = format!("notes/{}", name);
+        let content = match renderer.format() {
+            ExportFormat::Json if !renderer.has_template() => {
+                serde_json::to_string_pretty(note).map_err(json_error)?
+            }
+            _ => renderer.render(note)?,
+        };
+
+        zip.start_file(file.as_str(), options).map_err(zip_error)?;
+        zip.write_all(content.as_bytes())?;
+
+        entries.push(ManifestEntry {
+            id: note.id,
+            title: note.title.clone(),
+            file,
+            hash: hash(note),
+            created_at: note.created_at,
+            updated_at: note.updated_at,
+        });
+    }
+
+    let manifest = ArchiveManifest {
+        kiro_export: EXPORT_VERSION,
+        exported_at: document.exported_at,
+        format: renderer.format(),
+        notes: entries,
+    };
+
+    zip.start_file(MANIFEST_NAME, options).map_err(zip_error)?;
+    zip.write_all(serde_json::to_string_pretty(&manifest).map_err(json_error)?.as_bytes())?;
+
+    zip.start_file(DATA_NAME, options).map_err(zip_error)?;
+    zip.write_all(serde_json::to_string_pretty(document).map_err(json_error)?.as_bytes())?;
+
+    zip.finish().map_err(zip_error)?;
+    Ok(())
+}
+
+/// Zip the contents of `dir` into `path`, with paths relative to `dir`.
+/// Used for static sites, which aren't importable and so get no manifest.
+pub fn write_dir_archive(dir: &Path, path: &Path) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let mut entries: Vec<_> = std::fs::read_dir(&current)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .collect();
+        entries.sort();
+        for entry in entries {
+            if entry.is_dir() {
+                pending.push(entry);
+                continue;
+            }
+            let name = entry
+                .strip_prefix(dir)
+                .map_err(|_| KiroError::InvalidPath(entry.display().to_string()))?
+                .to_string_lossy()
+                .replace('\\', "/");
+            zip.start_file(name, options).map_err(zip_error)?;
+            zip.write_all(&std::fs::read(&entry)?)?;
+        }
+    }
+
+    zip.finish().map_err(zip_error)?;
+    Ok(())
+}
+
+/// Read the notes back out of an export archive, returning None if the
+/// file isn't a Kiro archive
+pub fn read_archive(path: &Path) -> Option<ExportDocument> {
+    let mut archive = ZipArchive::new(File::open(path).ok()?).ok()?;
+
+    // Only archives with a manifest are treated as Kiro exports
+    let mut manifest = String::new();
+    archive.by_name(MANIFEST_NAME).ok()?.read_to_string(&mut manifest).ok()?;
+    serde_json::from_str::<ArchiveManifest>(&manifest).ok()?;
+
+    let mut data = String::new();
+    archive.by_name(DATA_NAME).ok()?.read_to_string(&mut data).ok()?;
+    serde_json::from_str(&data).ok()
+}
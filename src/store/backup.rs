@@ -0,0 +1,172 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+
+use super::{NoteStore, StoreEvent, SCHEMA_VERSION};
+use crate::error::{KiroError, Result};
+
+/// Pages copied per backup step. Small steps keep the database usable
+/// while a large backup runs.
+const PAGES_PER_STEP: std::os::raw::c_int = 256;
+
+/// File name prefix of automatic backups. Only these are rotated.
+const AUTO_PREFIX: &str = "notes-";
+
+/// File name prefix of the backups taken before a schema migration
+pub(super) const PRE_MIGRATION_PREFIX: &str = "pre-migration-";
+
+impl NoteStore {
+    /// Copy the database to `path` using SQLite's online backup API
+    pub fn backup_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut dest = Connection::open(path)?;
+        let backup = Backup::new(&self.conn, &mut dest)?;
+        backup.run_to_completion(PAGES_PER_STEP, Duration::from_millis(10), None)?;
+        Ok(())
+    }
+
+    /// Replace the contents of this store with a backup, after checking
+    /// that it is a Kiro database this version can read
+    pub fn restore_from(&mut self, path: &Path) -> Result<()> {
+        validate_backup(path)?;
+
+        let source = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let backup = Backup::new(&source, &mut self.conn)?;
+        backup.run_to_completion(PAGES_PER_STEP, Duration::from_millis(10), None)?;
+        drop(backup);
+
+        // Bring an older backup up to the current schema
+        self.migrate()?;
+        self.load_key_state()?;
+        self.publish(StoreEvent::Reloaded);
+        Ok(())
+    }
+
+    /// Take an automatic backup into `dir` and delete all but the newest
+    /// `keep` automatic backups. Returns the new backup's path.
+    pub fn auto_backup(&self, dir: &Path, keep: usize) -> Result<PathBuf> {
+        let path = dir.join(format!(
+            "{}{}.db",
+            AUTO_PREFIX,
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ));
+        self.backup_to(&path)?;
+        rotate_backups(dir, keep)?;
+        Ok(path)
+    }
+}
+
+/// Check that a file is a readable Kiro database no newer than this build.
+/// Returns its schema version.
+pub fn validate_backup(path: &Path) -> Result<i32> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| KiroError::InvalidBackup(format!("{}: {}", path.display(), e)))?;
+
+    let integrity: String = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| KiroError::InvalidBackup(format!("{}: {}", path.display(), e)))?;
+    if integrity != "ok" {
+        return Err(KiroError::InvalidBackup(format!("{}: {}", path.display(), integrity)));
+    }
+
+    let has_notes: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'notes'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_notes {
+        return Err(KiroError::InvalidBackup(format!(
+            "{}: not a Kiro database",
+            path.display()
+        )));
+    }
+
+    let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version > SCHEMA_VERSION {
+        return Err(KiroError::InvalidBackup(format!(
+            "{}: schema version {} is newer than supported version {}",
+            path.display(),
+            version,
+            SCHEMA_VERSION
+        )));
+    }
+
+    Ok(version)
+}
+
+/// Automatic backups in `dir`, newest first
+pub fn list_backups(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<PathBuf> = read_dir
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(AUTO_PREFIX) && n.ends_with(".db"))
+        })
+        .collect();
+
+    // Names embed the timestamp, so they sort chronologically
+    backups.sort();
+    backups.reverse();
+    backups
+}
+
+/// Time of the newest automatic backup in `dir`
+pub fn last_backup_time(dir: &Path) -> Option<DateTime<Utc>> {
+    let newest = list_backups(dir).into_iter().next()?;
+    std::fs::metadata(newest)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(DateTime::<Utc>::from)
+}
+
+/// Delete the automatic and pre-migration backups in `dir` that hold
+/// unencrypted notes. Returns how many were deleted.
+pub fn remove_unencrypted_backups(dir: &Path) -> Result<usize> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Ok(0);
+    };
+
+    let mut removed = 0;
+    for path in read_dir.filter_map(|e| e.ok()).map(|e| e.path()) {
+        let is_backup = path.file_name().and_then(|n| n.to_str()).is_some_and(|n| {
+            (n.starts_with(AUTO_PREFIX) || n.starts_with(PRE_MIGRATION_PREFIX)) && n.ends_with(".db")
+        });
+        if is_backup && validate_backup(&path).is_ok() && !is_encrypted_backup(&path)? {
+            std::fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+fn is_encrypted_backup(path: &Path) -> Result<bool> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let has_meta: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'meta'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_meta {
+        return Ok(false);
+    }
+    Ok(conn.query_row("SELECT COUNT(*) > 0 FROM meta WHERE key = 'salt'", [], |row| row.get(0))?)
+}
+
+fn rotate_backups(dir: &Path, keep: usize) -> Result<()> {
+    for old in list_backups(dir).into_iter().skip(keep.max(1)) {
+        std::fs::remove_file(old)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,292 @@
+use std::sync::{Arc, RwLock};
+
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params, Connection};
+use zeroize::Zeroizing;
+
+use super::{NoteStore, OptionalExt};
+use crate::error::{KiroError, Result};
+
+/// Prefix of every encrypted value, followed by the nonce and ciphertext
+const MAGIC: &[u8] = b"KRO1";
+const NONCE_LEN: usize = 12;
+pub(super) const SALT_LEN: usize = 16;
+
+/// Known plaintext stored encrypted in `meta` to check a passphrase
+const VERIFIER: &str = "kiro";
+
+/// Key derived from a passphrase
+pub(super) struct Cipher(ChaCha20Poly1305);
+
+impl Cipher {
+    pub(super) fn derive(passphrase: &str, salt: &[u8]) -> Result<Self> {
+        // The cipher wipes its own copy of the key on drop
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut *key)
+            .map_err(|e| KiroError::Encryption(e.to_string()))?;
+        Ok(Self(ChaCha20Poly1305::new(Key::from_slice(&*key))))
+    }
+
+    /// Encrypt with a fresh nonce. Returns the nonce followed by the ciphertext.
+    pub(super) fn encrypt(&self, plaintext: &str) -> Result<Vec<u8>> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .0
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| KiroError::Encryption(e.to_string()))?;
+
+        let mut out = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    pub(super) fn decrypt(&self, data: &[u8]) -> Result<String> {
+        if data.len() < NONCE_LEN {
+            return Err(KiroError::Encryption("malformed encrypted value".into()));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+
+        let plaintext = self
+            .0
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| KiroError::WrongPassphrase)?;
+        String::from_utf8(plaintext).map_err(|e| KiroError::Encryption(e.to_string()))
+    }
+}
+
+/// Random salt for deriving a new key
+pub(super) fn new_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+fn seal(cipher: &Cipher, plaintext: &str) -> Result<Vec<u8>> {
+    let mut out = MAGIC.to_vec();
+    out.extend(cipher.encrypt(plaintext)?);
+    Ok(out)
+}
+
+fn unseal(cipher: &Cipher, data: &[u8]) -> Result<String> {
+    let data = data
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| KiroError::Encryption("malformed encrypted value".into()))?;
+    cipher.decrypt(data)
+}
+
+/// Whether the store is encrypted, and if so whether the key is loaded
+pub(super) enum KeyState {
+    Plaintext,
+    Locked,
+    Unlocked(Cipher),
+}
+
+pub(super) type SharedKey = Arc<RwLock<KeyState>>;
+
+fn is_encrypted_value(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn function_error(e: KiroError) -> rusqlite::Error {
+    rusqlite::Error::UserFunctionError(Box::new(e))
+}
+
+/// Register `kiro_encrypt(text)` and `kiro_decrypt(value)` on the
+/// connection. Queries wrap the title and body columns in these, so the
+/// rest of the store works the same whether or not encryption is on.
+pub(super) fn register_functions(conn: &Connection, key: SharedKey) -> Result<()> {
+    let encrypt_key = key.clone();
+    conn.create_scalar_function("kiro_encrypt", 1, FunctionFlags::SQLITE_UTF8, move |ctx| {
+        let text = match ctx.get_raw(0) {
+            ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
+            // Already encrypted, or not text at all
+            other => return Ok(Value::from(other)),
+        };
+
+        let state = encrypt_key.read().map_err(|e| function_error(e.into()))?;
+        match &*state {
+            KeyState::Plaintext => Ok(Value::Text(text)),
+            KeyState::Locked => Err(function_error(KiroError::Locked)),
+            KeyState::Unlocked(cipher) => seal(cipher, &text)
+                .map(Value::Blob)
+                .map_err(function_error),
+        }
+    })?;
+
+    // Not SQLITE_DETERMINISTIC: the result depends on the key state, so
+    // SQLite must not reuse it across calls or in indexes
+    conn.create_scalar_function("kiro_decrypt", 1, FunctionFlags::SQLITE_UTF8, move |ctx| {
+        let data = match ctx.get_raw(0) {
+            ValueRef::Blob(b) if is_encrypted_value(b) => b,
+            other => return Ok(Value::from(other)),
+        };
+
+        let state = key.read().map_err(|e| function_error(e.into()))?;
+        match &*state {
+            KeyState::Unlocked(cipher) => unseal(cipher, data)
+                .map(Value::Text)
+                .map_err(function_error),
+            _ => Err(function_error(KiroError::Locked)),
+        }
+    })?;
+
+    Ok(())
+}
+
+impl NoteStore {
+    /// Whether notes are stored encrypted
+    pub fn is_encrypted(&self) -> bool {
+        !matches!(*self.key.read().unwrap_or_else(|e| e.into_inner()), KeyState::Plaintext)
+    }
+
+    /// Whether notes are encrypted and the passphrase hasn't been entered
+    pub fn is_locked(&self) -> bool {
+        matches!(*self.key.read().unwrap_or_else(|e| e.into_inner()), KeyState::Locked)
+    }
+
+    /// Fail with `KiroError::Locked` unless note contents can be read
+    pub fn ensure_unlocked(&self) -> Result<()> {
+        if self.is_locked() {
+            return Err(KiroError::Locked);
+        }
+        Ok(())
+    }
+
+    /// Set the key state from the database: locked if it has encryption
+    /// metadata, plaintext otherwise
+    pub(super) fn load_key_state(&self) -> Result<()> {
+        let state = if self.meta_blob("salt")?.is_some() {
+            KeyState::Locked
+        } else {
+            KeyState::Plaintext
+        };
+        self.set_key_state(state);
+        Ok(())
+    }
+
+    /// Derive the key from `passphrase` and unlock the store
+    pub fn unlock_notes(&self, passphrase: &str) -> Result<()> {
+        let salt = self
+            .meta_blob("salt")?
+            .ok_or_else(|| KiroError::Encryption("notes are not encrypted".into()))?;
+        let verifier = self
+            .meta_blob("verifier")?
+            .ok_or_else(|| KiroError::Encryption("missing passphrase verifier".into()))?;
+
+        let cipher = Cipher::derive(passphrase, &salt)?;
+        if unseal(&cipher, &verifier)? != VERIFIER {
+            return Err(KiroError::WrongPassphrase);
+        }
+
+        self.set_key_state(KeyState::Unlocked(cipher));
+        Ok(())
+    }
+
+    /// Forget the master key and the keys of any unlocked secret notes
+    pub fn lock_notes(&self) {
+        if self.is_encrypted() {
+            self.set_key_state(KeyState::Locked);
+        }
+        self.lock_secret_notes();
+    }
+
+    /// Encrypt every note in place with a key derived from `passphrase`
+    pub fn enable_encryption(&self, passphrase: &str) -> Result<()> {
+        if self.is_encrypted() {
+            return Err(KiroError::Encryption("notes are already encrypted".into()));
+        }
+        if passphrase.is_empty() {
+            return Err(KiroError::validation("passphrase", "must not be empty"));
+        }
+
+        let salt = new_salt();
+        let cipher = Cipher::derive(passphrase, &salt)?;
+        let verifier = seal(&cipher, VERIFIER)?;
+        self.set_key_state(KeyState::Unlocked(cipher));
+
+        let result = (|| -> Result<()> {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute(
+                "UPDATE notes SET title = kiro_encrypt(title), body = kiro_encrypt(body),
+                 import_hash = kiro_encrypt(import_hash)",
+                [],
+            )?;
+            tx.execute("INSERT OR REPLACE INTO meta (key, value) VALUES ('salt', ?)", [&salt[..]])?;
+            tx.execute("INSERT OR REPLACE INTO meta (key, value) VALUES ('verifier', ?)", [&verifier])?;
+            tx.commit()?;
+            Ok(())
+        })();
+
+        if result.is_err() {
+            self.set_key_state(KeyState::Plaintext);
+            return result;
+        }
+        self.check_encrypted()?;
+        self.scrub()
+    }
+
+    /// Fail unless every title, body and import hash is encrypted and can
+    /// be decrypted with the current key
+    pub fn check_encrypted(&self) -> Result<()> {
+        let plaintext: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM notes WHERE typeof(title) = 'text'
+             OR (encrypted = 0 AND typeof(body) = 'text') OR typeof(import_hash) = 'text'",
+            [],
+            |row| row.get(0),
+        )?;
+        if plaintext > 0 {
+            return Err(KiroError::Encryption(format!("{} notes are still unencrypted", plaintext)));
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT kiro_decrypt(title), CASE WHEN encrypted THEN '' ELSE kiro_decrypt(body) END,
+             kiro_decrypt(import_hash) FROM notes",
+        )?;
+        let mut rows = stmt.query([])?;
+        while rows.next()?.is_some() {}
+        Ok(())
+    }
+
+    /// Rewrite the database file so no freed page still holds an old copy
+    /// of a note. `secure_delete` is on for every connection, see
+    /// `NoteStore::open`.
+    pub(super) fn scrub(&self) -> Result<()> {
+        self.conn.execute_batch("VACUUM")?;
+        Ok(())
+    }
+
+    /// Decrypt every note in place and remove the passphrase
+    pub fn disable_encryption(&self, passphrase: &str) -> Result<()> {
+        self.unlock_notes(passphrase)?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE notes SET title = kiro_decrypt(title), body = kiro_decrypt(body),
+             import_hash = kiro_decrypt(import_hash)",
+            [],
+        )?;
+        tx.execute("DELETE FROM meta WHERE key IN ('salt', 'verifier')", [])?;
+        tx.commit()?;
+
+        self.set_key_state(KeyState::Plaintext);
+        self.scrub()
+    }
+
+    fn set_key_state(&self, state: KeyState) {
+        *self.key.write().unwrap_or_else(|e| e.into_inner()) = state;
+    }
+
+    fn meta_blob(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .conn
+            .query_row("SELECT value FROM meta WHERE key = ?", params![key], |row| row.get(0))
+            .optional()?)
+    }
+}
@@ -0,0 +1,42 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, MutexGuard};
+
+use serde::Serialize;
+
+use super::NoteStore;
+
+/// A change written through a `NoteStore`, published after it succeeds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StoreEvent {
+    Created { id: i64 },
+    Updated { id: i64 },
+    Deleted { id: i64 },
+    /// Notes added by an import, in import order
+    Imported { ids: Vec<i64> },
+    BulkDeleted { ids: Vec<i64> },
+    /// Any note may have changed, e.g. after restoring a backup
+    Reloaded,
+}
+
+pub(super) type Subscribers = Mutex<Vec<Sender<StoreEvent>>>;
+
+impl NoteStore {
+    /// Receive every change made through this store from now on. Dropping
+    /// the receiver unsubscribes.
+    pub fn subscribe(&self) -> Receiver<StoreEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers().push(sender);
+        receiver
+    }
+
+    pub(super) fn publish(&self, event: StoreEvent) {
+        self.subscribers()
+            .retain(|sender| sender.send(event.clone()).is_ok());
+    }
+
+    fn subscribers(&self) -> MutexGuard<'_, Vec<Sender<StoreEvent>>> {
+        // A panic while holding the lock can't leave the list half-updated
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use pulldown_cmark::{html, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+use super::note::Note;
+use crate::config::Config;
+use crate::error::{KiroError, Result};
+use crate::template::{split_header, Context, Template, Value};
+
+/// File extension of export templates
+const TEMPLATE_EXTENSION: &str = "tmpl";
+
+/// Variables an export template can use, see `note_context`
+const TEMPLATE_VARIABLES: &[&str] = &["id", "title", "body", "body_html", "created_at", "updated_at", "word_count"];
+
+/// Keys of an export template's header
+const HEADER_KEYS: &[&str] = &["filename"];
+
+/// Version written to the `kiro_export` field of JSON exports
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// One `.md` file per note with YAML front matter
+    #[default]
+    Markdown,
+    /// One `.txt` file per note
+    PlainText,
+    /// One standalone `.html` page per note
+    Html,
+    /// A single JSON document that can be imported again
+    Json,
+    /// A static HTML site with an index page and client-side search
+    Site,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::PlainText => "txt",
+            ExportFormat::Html => "html",
+            ExportFormat::Json => "json",
+            ExportFormat::Site => "html",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Write a single timestamped .zip with a manifest instead of loose files
+    pub archive: bool,
+    /// Directory to export into. Defaults to ~/Downloads/kiro-export.
+    pub destination: Option<PathBuf>,
+    /// Name of an export template to render notes with instead of `format`
+    pub template: Option<String>,
+    /// Template for file names, overriding the export template's own
+    pub filename_template: Option<String>,
+}
+
+/// JSON export document. `kiro_export` holds the format version and marks
+/// the file as importable.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportDocument {
+    pub kiro_export: u32,
+    pub exported_at: DateTime<Utc>,
+    pub notes: Vec<Note>,
+}
+
+impl ExportDocument {
+    pub fn new(notes: Vec<Note>) -> Self {
+        Self {
+            kiro_export: EXPORT_VERSION,
+            exported_at: Utc::now(),
+            notes,
+        }
+    }
+
+    /// Read a JSON export or zip archive, returning None if the file
+    /// isn't one
+    pub fn read(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => {
+                let contents = std::fs::read_to_string(path).ok()?;
+                serde_json::from_str(&contents).ok()
+            }
+            Some("zip") => super::archive::read_archive(path),
+            _ => None,
+        }
+    }
+}
+
+/// A user-defined export template from the config directory.
+///
+/// The file may start with a header block setting the file name template.
+/// A leading `---` block with any other key is YAML front matter for the
+/// output and is rendered like the rest of the template.
+///
+/// ```text
+/// ---
+/// filename: {{created_at|date:"%Y-%m-%d"}}-{{title|slug}}.md
+/// ---
+/// # {{title}}
+/// {{body}}
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExportTemplate {
+    body: Template,
+    filename: Option<Template>,
+}
+
+impl ExportTemplate {
+    /// Export templates live in ~/.config/kiro/export-templates/<name>.tmpl
+    pub fn dir() -> Option<PathBuf> {
+        Config::config_dir().map(|p| p.join("export-templates"))
+    }
+
+    pub fn parse(source: &str) -> Result<Self> {
+        let (header, body) = split_header(source, HEADER_KEYS);
+        let filename = header
+            .iter()
+            .rev()
+            .find(|(key, _)| *key == "filename")
+            .map(|(_, value)| parse_template(value))
+            .transpose()?;
+
+        Ok(Self {
+            body: parse_template(body)?,
+            filename,
+        })
+    }
+
+    pub fn load(name: &str) -> Result<Self> {
+        if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+            return Err(KiroError::InvalidTemplate(format!("invalid template name '{}'", name)));
+        }
+
+        let path = Self::dir()
+            .ok_or_else(|| KiroError::InvalidPath("No config directory".to_string()))?
+            .join(format!("{}.{}", name, TEMPLATE_EXTENSION));
+        let source = std::fs::read_to_string(&path)
+            .map_err(|_| KiroError::InvalidTemplate(format!("template '{}' not found", name)))?;
+
+        Self::parse(&source)
+    }
+
+    /// Names of all export templates, sorted
+    pub fn list() -> Vec<String> {
+        let Some(dir) = Self::dir() else {
+            return Vec::new();
+        };
+        let Ok(read_dir) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut names: Vec<String> = read_dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(TEMPLATE_EXTENSION))
+            .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(String::from))
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+/// Parse an export or file name template, refusing variables that notes
+/// don't have instead of silently rendering them empty
+fn parse_template(source: &str) -> Result<Template> {
+    let template = Template::parse(source)?;
+    if let Some(name) = template
+        .variables()
+        .into_iter()
+        .find(|name| !TEMPLATE_VARIABLES.contains(&name.as_str()))
+    {
+        return Err(KiroError::InvalidTemplate(format!(
+            "unknown variable '{}'; export templates can use {}",
+            name,
+            TEMPLATE_VARIABLES.join(", ")
+        )));
+    }
+    Ok(template)
+}
+
+/// Variables available to export templates, see `TEMPLATE_VARIABLES`.
+/// Notes have no tags, so there is no `tags` variable.
+fn note_context(note: &Note) -> Context {
+    let mut context = Context::new();
+    context.insert("id".to_string(), Value::Number(note.id));
+    context.insert("title".to_string(), note.title.as_str().into());
+    context.insert("body".to_string(), note.body.as_str().into());
+    context.insert("body_html".to_string(), markdown_to_html(&note.body).into());
+    context.insert("created_at".to_string(), note.created_at.into());
+    context.insert("updated_at".to_string(), note.updated_at.into());
+    context.insert(
+        "word_count".to_string(),
+        Value::Number(note.body.split_whitespace().count() as i64),
+    );
+    context
+}
+
+/// Renders notes for export, either in a built-in format or through an
+/// export template
+#[derive(Debug, Clone)]
+pub struct NoteRenderer {
+    format: ExportFormat,
+    template: Option<ExportTemplate>,
+    filename: Option<Template>,
+}
+
+impl NoteRenderer {
+    pub fn new(options: &ExportOptions) -> Result<Self> {
+        let template = options
+            .template
+            .as_deref()
+            .map(ExportTemplate::load)
+            .transpose()?;
+
+        let filename = match &options.filename_template {
+            Some(source) => Some(parse_template(source)?),
+            None => template.as_ref().and_then(|t| t.filename.clone()),
+        };
+
+        Ok(Self {
+            format: options.format,
+            template,
+            filename,
+        })
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        self.format
+    }
+
+    /// Whether notes are rendered through a user template
+    pub fn has_template(&self) -> bool {
+        self.template.is_some()
+    }
+
+    pub fn filename(&self, note: &Note) -> Result<String> {
+        let Some(template) = &self.filename else {
+            return Ok(note_filename(note, self.format.extension()));
+        };
+
+        let rendered = template.render(&note_context(note))?;
+        let safe: String = rendered
+            .chars()
+            .map(|c| if c.is_control() || matches!(c, '/' | '\\' | ':') { '-' } else { c })
+            .collect();
+        let safe = safe.trim().trim_start_matches('.');
+
+        if safe.is_empty() {
+            Ok(note_filename(note, self.format.extension()))
+        } else {
+            Ok(safe.to_string())
+        }
+    }
+
+    pub fn render(&self, note: &Note) -> Result<String> {
+        match &self.template {
+            Some(template) => template.body.render(&note_context(note)),
+            None => Ok(render_note(note, self.format)),
+        }
+    }
+}
+
+/// Make `name` unique within one export by appending the note id
+pub fn unique_filename(used: &mut HashSet<String>, name: String, id: i64) -> String {
+    if used.insert(name.clone()) {
+        return name;
+    }
+
+    let unique = match name.rsplit_once('.') {
+        Some((stem, ext)) => format!("{}-{}.{}", stem, id, ext),
+        None => format!("{}-{}", name, id),
+    };
+    used.insert(unique.clone());
+    unique
+}
+
+/// File name for an exported note: note-{id}-{title}.{ext}
+pub fn note_filename(note: &Note, extension: &str) -> String {
+    let safe_title: String = note
+        .title
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
+        .take(50)
+        .collect();
+    let safe_title = safe_title.trim().replace(' ', "-");
+    if safe_title.is_empty() {
+        format!("note-{}.{}", note.id, extension)
+    } else {
+        format!("note-{}-{}.{}", note.id, safe_title, extension)
+    }
+}
+
+/// Render a single note in a per-note format. JSON exports are written as
+/// one document and are handled by the caller.
+pub fn render_note(note: &Note, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown | ExportFormat::Json => render_markdown(note),
+        ExportFormat::PlainText => render_plain_text(note),
+        ExportFormat::Html | ExportFormat::Site => render_html(note),
+    }
+}
+
+fn render_markdown(note: &Note) -> String {
+    // A JSON string is also a valid YAML double-quoted scalar
+    let title = serde_json::to_string(&note.title).unwrap_or_default();
+    format!(
+        "---\ntitle: {}\ncreated: {}\nupdated: {}\n---\n\n{}",
+        title,
+        note.created_at.to_rfc3339(),
+        note.updated_at.to_rfc3339(),
+        note.body
+    )
+}
+
+fn render_plain_text(note: &Note) -> String {
+    format!(
+        "{}\nCreated: {} | Updated: {}\n\n{}",
+        note.title,
+        note.created_at.format("%Y-%m-%d %H:%M"),
+        note.updated_at.format("%Y-%m-%d %H:%M"),
+        note.body
+    )
+}
+
+fn render_html(note: &Note) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>{title}</title>
+<style>
+body {{ max-width: 48rem; margin: 2rem auto; padding: 0 1rem; font-family: system-ui, sans-serif; line-height: 1.6; }}
+.meta {{ color: #666; font-size: 0.875rem; }}
+pre {{ background: #f4f4f4; padding: 0.75rem; overflow-x: auto; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p class="meta">Created: {created} | Updated: {updated}</p>
+{body}
+</body>
+</html>
+"#,
+        title = escape_html(&note.title),
+        created = note.created_at.format("%Y-%m-%d %H:%M"),
+        updated = note.updated_at.format("%Y-%m-%d %H:%M"),
+        body = markdown_to_html(&note.body),
+    )
+}
+
+/// Render a Markdown body to an HTML fragment. Raw HTML in the note is
+/// shown as text, see `escape_raw_html`.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut out = String::new();
+    html::push_html(&mut out, drop_unsafe_links(markdown_parser(markdown).map(escape_raw_html)));
+    out
+}
+
+/// Parser with the Markdown extensions used by every HTML export
+pub(crate) fn markdown_parser(markdown: &str) -> Parser<'_> {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    Parser::new_ext(markdown, options)
+}
+
+/// Turn raw HTML into text so exported pages can't run a note's scripts or
+/// break the page layout
+pub(crate) fn escape_raw_html(event: Event<'_>) -> Event<'_> {
+    match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        event => event,
+    }
+}
+
+/// Drop links and images whose URL could run script, such as
+/// `javascript:`, keeping their text. Only http, https, mailto and relative
+/// URLs are kept.
+pub(crate) fn drop_unsafe_links<'e>(events: impl Iterator<Item = Event<'e>>) -> impl Iterator<Item = Event<'e>> {
+    // Whether each open link or image was dropped; an image can sit in a link
+    let mut dropped: Vec<bool> = Vec::new();
+    events.filter(move |event| match event {
+        Event::Start(Tag::Link { dest_url, .. }) | Event::Start(Tag::Image { dest_url, .. }) => {
+            let safe = is_safe_url(dest_url);
+            dropped.push(!safe);
+            safe
+        }
+        Event::End(TagEnd::Link) | Event::End(TagEnd::Image) => !dropped.pop().unwrap_or(false),
+        _ => true,
+    })
+}
+
+fn is_safe_url(url: &str) -> bool {
+    // Browsers ignore whitespace and control characters in the scheme
+    let url: String = url.chars().filter(|c| !c.is_whitespace() && !c.is_control()).collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(end) if url[end..].starts_with(':') => {
+            matches!(url[..end].to_ascii_lowercase().as_str(), "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
@@ -0,0 +1,80 @@
+//! Daily notes: at most one note per date, linked by the `journal_date`
+//! column rather than by title, so renaming a daily note keeps its date
+
+use std::collections::HashMap;
+
+use chrono::{Local, NaiveDate, Utc};
+use rusqlite::params;
+
+use super::{Note, NoteStore, NoteTemplate, OptionalExt, StoreEvent};
+use crate::config::JournalSettings;
+use crate::error::{KiroError, Result};
+
+impl NoteStore {
+    /// The daily note for `date`, if there is one
+    pub fn daily_note(&self, date: NaiveDate) -> Result<Option<Note>> {
+        let id: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT id FROM notes WHERE journal_date = ?",
+                [date.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match id {
+            Some(id) => self.get(id),
+            None => Ok(None),
+        }
+    }
+
+    /// Create the daily note for `date`; fails if it already exists
+    pub fn create_daily_note(&self, date: NaiveDate, title: &str, body: &str) -> Result<i64> {
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO notes (title, body, created_at, updated_at, journal_date)
+             VALUES (kiro_encrypt(?), kiro_encrypt(?), ?, ?, ?)",
+            params![title, body, now, now, date.to_string()],
+        )?;
+        let id = self.conn.last_insert_rowid();
+        self.publish(StoreEvent::Created { id });
+        Ok(id)
+    }
+
+    /// The daily note for `date`, created from `settings` if there is none
+    /// yet. The template's `{{title}}` is the formatted title and its other
+    /// prompts are left empty.
+    pub fn open_daily_note(&self, date: NaiveDate, settings: &JournalSettings) -> Result<Note> {
+        if let Some(note) = self.daily_note(date)? {
+            return Ok(note);
+        }
+
+        let title = settings.title(date);
+        let body = match &settings.template {
+            Some(name) => {
+                let vars = HashMap::from([("title".to_string(), title.clone())]);
+                let now = date.and_time(Local::now().time());
+                NoteTemplate::load(name)?.render(&vars, now)?.body
+            }
+            None => String::new(),
+        };
+        let id = self.create_daily_note(date, &title, &body)?;
+        self.get(id)?.ok_or(KiroError::NoteNotFound(id))
+    }
+
+    /// The nearest daily note before `date`, or after it if `forward`
+    pub fn adjacent_daily_note(&self, date: NaiveDate, forward: bool) -> Result<Option<Note>> {
+        let sql = if forward {
+            "SELECT id FROM notes WHERE journal_date > ? ORDER BY journal_date ASC LIMIT 1"
+        } else {
+            "SELECT id FROM notes WHERE journal_date < ? ORDER BY journal_date DESC LIMIT 1"
+        };
+        let id: Option<i64> = self
+            .conn
+            .query_row(sql, [date.to_string()], |row| row.get(0))
+            .optional()?;
+        match id {
+            Some(id) => self.get(id),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use chrono::Utc;
+use rusqlite::Connection;
+
+use super::backup::PRE_MIGRATION_PREFIX;
+use super::NoteStore;
+use crate::error::{KiroError, Result};
+
+/// One step of the schema history. `up` runs inside a transaction that
+/// also records the new `user_version`, so a failed step leaves the
+/// database at the previous version.
+struct Migration {
+    version: i32,
+    description: &'static str,
+    up: fn(&Connection) -> Result<()>,
+}
+
+/// Every schema change, oldest first. Append new steps here; never edit
+/// a step that has shipped.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "notes table with import hash",
+        up: initial_schema,
+    },
+    Migration {
+        version: 2,
+        description: "meta table for encryption settings",
+        up: meta_table,
+    },
+    Migration {
+        version: 3,
+        description: "secret notes",
+        up: secret_notes,
+    },
+    Migration {
+        version: 4,
+        description: "daily notes",
+        up: daily_notes,
+    },
+];
+
+/// Current database schema version, stored in `PRAGMA user_version`
+pub const SCHEMA_VERSION: i32 = MIGRATIONS.len() as i32;
+
+impl NoteStore {
+    /// Bring the database up to `SCHEMA_VERSION`, backing it up first if
+    /// it already holds notes from an older version
+    pub(super) fn migrate(&self) -> Result<()> {
+        let current = self.schema_version()?;
+        if current > SCHEMA_VERSION {
+            return Err(KiroError::UnsupportedSchema(current));
+        }
+        if current == SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        if self.has_notes_table()? {
+            self.pre_migration_backup(current)?;
+        }
+
+        apply_migrations(&self.conn, current)
+    }
+
+    /// Schema version recorded in the database file
+    pub fn schema_version(&self) -> Result<i32> {
+        Ok(self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?)
+    }
+
+    fn has_notes_table(&self) -> Result<bool> {
+        Ok(self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'notes'",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    fn pre_migration_backup(&self, version: i32) -> Result<()> {
+        let path = self.backup_dir.join(format!(
+            "{}v{}-{}.db",
+            PRE_MIGRATION_PREFIX,
+            version,
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ));
+        self.backup_to(&path)
+    }
+}
+
+/// Run every step after version `current`, each in its own transaction
+fn apply_migrations(conn: &Connection, current: i32) -> Result<()> {
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        (migration.up)(&tx).map_err(|e| {
+            KiroError::MigrationFailed(format!(
+                "v{} ({}): {}",
+                migration.version, migration.description, e
+            ))
+        })?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// Version 1: the original notes table. Databases created before schema
+/// versioning report version 0 and may lack the `import_hash` column.
+fn initial_schema(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS notes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            import_hash TEXT
+        )",
+        [],
+    )?;
+
+    let has_import_hash: bool = conn
+        .prepare("PRAGMA table_info(notes)")?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|col| col == "import_hash");
+
+    if !has_import_hash {
+        conn.execute("ALTER TABLE notes ADD COLUMN import_hash TEXT", [])?;
+    }
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_updated ON notes(updated_at DESC)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_import_hash ON notes(import_hash)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Version 2: key/value metadata, used for the encryption salt and
+/// passphrase verifier
+fn meta_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS meta (
+            key TEXT PRIMARY KEY,
+            value BLOB NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Version 3: flag for notes whose body is sealed with their own passphrase
+fn secret_notes(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "ALTER TABLE notes ADD COLUMN encrypted INTEGER NOT NULL DEFAULT 0",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Version 4: the date a daily note belongs to, at most one note per date
+fn daily_notes(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE notes ADD COLUMN journal_date TEXT", [])?;
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_journal_date ON notes(journal_date)",
+        [],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BackupSettings;
+
+    /// The notes table as created before schema versioning, without
+    /// `import_hash`
+    fn baseline(conn: &Connection) {
+        conn.execute_batch(
+            "CREATE TABLE notes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                title TEXT NOT NULL,
+                body TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            INSERT INTO notes (title, body, created_at, updated_at)
+            VALUES ('Old', 'body', '2023-01-01T00:00:00+00:00', '2023-01-01T00:00:00+00:00');",
+        )
+        .unwrap();
+    }
+
+    fn user_version(conn: &Connection) -> i32 {
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap()
+    }
+
+    fn columns(conn: &Connection) -> Vec<String> {
+        conn.prepare("PRAGMA table_info(notes)")
+            .unwrap()
+            .query_map([], |row| row.get(1))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn versions_are_sequential() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as i32 + 1, "{}", migration.description);
+        }
+        assert_eq!(SCHEMA_VERSION, MIGRATIONS.last().unwrap().version);
+    }
+
+    #[test]
+    fn migrates_baseline_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        baseline(&conn);
+        assert_eq!(user_version(&conn), 0);
+
+        apply_migrations(&conn, 0).unwrap();
+
+        assert_eq!(user_version(&conn), SCHEMA_VERSION);
+        let columns = columns(&conn);
+        for column in ["import_hash", "encrypted", "journal_date"] {
+            assert!(columns.iter().any(|c| c == column), "missing {}", column);
+        }
+        let title: String = conn.query_row("SELECT title FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(title, "Old");
+        let encrypted: bool = conn.query_row("SELECT encrypted FROM notes", [], |row| row.get(0)).unwrap();
+        assert!(!encrypted);
+    }
+
+    #[test]
+    fn migrates_from_every_version() {
+        for start in 0..SCHEMA_VERSION {
+            let conn = Connection::open_in_memory().unwrap();
+            baseline(&conn);
+            for migration in MIGRATIONS.iter().take(start as usize) {
+                (migration.up)(&conn).unwrap();
+            }
+            conn.pragma_update(None, "user_version", start).unwrap();
+
+            apply_migrations(&conn, start).unwrap();
+            assert_eq!(user_version(&conn), SCHEMA_VERSION, "from v{}", start);
+        }
+    }
+
+    #[test]
+    fn creates_empty_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply_migrations(&conn, 0).unwrap();
+        assert_eq!(user_version(&conn), SCHEMA_VERSION);
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn failed_step_keeps_previous_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        baseline(&conn);
+        apply_migrations(&conn, 0).unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION - 1).unwrap();
+
+        // The last step's column already exists, so running it again fails
+        assert!(apply_migrations(&conn, SCHEMA_VERSION - 1).is_err());
+        assert_eq!(user_version(&conn), SCHEMA_VERSION - 1);
+    }
+
+    #[test]
+    fn backs_up_into_configured_directory() {
+        let dir = std::env::temp_dir().join(format!("kiro-migration-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = dir.join("notes.db");
+        baseline(&Connection::open(&db).unwrap());
+
+        let backups = BackupSettings {
+            directory: Some(dir.join("elsewhere")),
+            ..BackupSettings::default()
+        };
+        let store = NoteStore::open(&db, &backups).unwrap();
+        assert_eq!(store.schema_version().unwrap(), SCHEMA_VERSION);
+
+        let names: Vec<String> = std::fs::read_dir(backups.dir_for(&db))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert!(names.iter().any(|n| n.starts_with("pre-migration-v0-")), "{:?}", names);
+        assert!(!dir.join("backups").exists());
+
+        drop(store);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
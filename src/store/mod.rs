@@ -1,64 +1,97 @@
+pub mod archive;
+pub mod backup;
+mod crypto;
+mod events;
+pub mod export;
+mod journal;
+pub mod migrations;
 pub mod note;
+pub mod note_template;
+mod secret;
+pub mod site;
+pub mod title;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection};
 use sha2::{Digest, Sha256};
 
+use crate::config::BackupSettings;
 use crate::error::{KiroError, Result};
+use crypto::{KeyState, SharedKey};
+use events::Subscribers;
+use export::{ExportDocument, NoteRenderer};
+use secret::SecretKeys;
+pub use events::StoreEvent;
+pub use export::{ExportFormat, ExportOptions, ExportTemplate};
+pub use migrations::SCHEMA_VERSION;
 pub use note::{Note, SearchResult};
+pub use note_template::{NoteTemplate, NoteTemplateInfo};
+pub use title::TitleStrategy;
+
+/// Note columns as stored; title and body may be encrypted. Secret note
+/// bodies are read separately once unlocked.
+const NOTE_COLUMNS: &str = "id, kiro_decrypt(title), \
+     CASE WHEN encrypted THEN '' ELSE kiro_decrypt(body) END, \
+     created_at, updated_at, kiro_decrypt(import_hash), encrypted, journal_date";
+
+/// How long to wait for another connection's write lock
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Search listing columns, with secret note bodies left out
+const SEARCH_COLUMNS: &str = "id, kiro_decrypt(title), \
+     CASE WHEN encrypted THEN '' ELSE kiro_decrypt(body) END, \
+     created_at, encrypted";
 
 pub struct NoteStore {
     conn: Connection,
+    path: PathBuf,
+    /// Where the backup taken before a schema migration goes
+    backup_dir: PathBuf,
+    key: SharedKey,
+    secrets: SecretKeys,
+    subscribers: Subscribers,
 }
 
 impl NoteStore {
-    pub fn open(path: &Path) -> Result<Self> {
+    /// Open the database at `path`, migrating it to the current schema.
+    /// `backups` decides where the pre-migration backup is written.
+    pub fn open(path: &Path, backups: &BackupSettings) -> Result<Self> {
         let conn = Connection::open(path)?;
-        let store = Self { conn };
-        store.init_schema()?;
+        // Zero deleted content instead of leaving it in free pages, where
+        // it would outlive encryption or a note being made secret
+        conn.pragma_update(None, "secure_delete", true)?;
+        // The app and the `kiro` CLI share the file, so wait for the other
+        // one's write to finish instead of failing with "database is locked"
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        let key = Arc::new(RwLock::new(KeyState::Plaintext));
+        crypto::register_functions(&conn, key.clone())?;
+
+        let store = Self {
+            conn,
+            path: path.to_path_buf(),
+            backup_dir: backups.dir_for(path),
+            key,
+            secrets: Mutex::new(HashMap::new()),
+            subscribers: Mutex::new(Vec::new()),
+        };
+        store.migrate()?;
+        store.load_key_state()?;
         Ok(store)
     }
 
-    fn init_schema(&self) -> Result<()> {
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS notes (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                title TEXT NOT NULL,
-                body TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                import_hash TEXT
-            )",
-            [],
-        )?;
-
-        // Add indexes
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_updated ON notes(updated_at DESC)",
-            [],
-        )?;
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_import_hash ON notes(import_hash)",
-            [],
-        )?;
-
-        // Migration: Add import_hash column if it doesn't exist
-        let has_import_hash: bool = self
-            .conn
-            .prepare("PRAGMA table_info(notes)")?
-            .query_map([], |row| row.get::<_, String>(1))?
-            .filter_map(|r| r.ok())
-            .any(|col| col == "import_hash");
-
-        if !has_import_hash {
-            self.conn
-                .execute("ALTER TABLE notes ADD COLUMN import_hash TEXT", [])?;
-        }
+    /// Path of the database file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
 
-        Ok(())
+    /// Apply changed backup settings to later pre-migration backups
+    pub fn set_backup_settings(&mut self, backups: &BackupSettings) {
+        self.backup_dir = backups.dir_for(&self.path);
     }
 
     pub fn count(&self) -> Result<usize> {
@@ -68,23 +101,27 @@ impl NoteStore {
         Ok(count as usize)
     }
 
+    /// Ids of all notes, newest first
+    pub fn ids(&self) -> Result<Vec<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM notes ORDER BY created_at DESC")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(ids)
+    }
+
     pub fn get(&self, id: i64) -> Result<Option<Note>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, title, body, created_at, updated_at, import_hash FROM notes WHERE id = ?",
-        )?;
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM notes WHERE id = ?", NOTE_COLUMNS))?;
 
-        let note = stmt
-            .query_row([id], |row| {
-                Ok(Note {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    body: row.get(2)?,
-                    created_at: parse_datetime(&row.get::<_, String>(3)?),
-                    updated_at: parse_datetime(&row.get::<_, String>(4)?),
-                    import_hash: row.get(5)?,
-                })
-            })
-            .optional()?;
+        let mut note = stmt.query_row([id], read_note).optional()?;
+        if let Some(note) = note.as_mut() {
+            self.reveal_secret(note)?;
+        }
 
         Ok(note)
     }
@@ -96,26 +133,20 @@ impl NoteStore {
 
         let placeholders: String = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
         let sql = format!(
-            "SELECT id, title, body, created_at, updated_at, import_hash FROM notes WHERE id IN ({}) ORDER BY updated_at DESC",
-            placeholders
+            "SELECT {} FROM notes WHERE id IN ({}) ORDER BY updated_at DESC",
+            NOTE_COLUMNS, placeholders
         );
 
         let mut stmt = self.conn.prepare(&sql)?;
         let params: Vec<&dyn rusqlite::ToSql> = ids.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
 
-        let notes = stmt
-            .query_map(params.as_slice(), |row| {
-                Ok(Note {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    body: row.get(2)?,
-                    created_at: parse_datetime(&row.get::<_, String>(3)?),
-                    updated_at: parse_datetime(&row.get::<_, String>(4)?),
-                    import_hash: row.get(5)?,
-                })
-            })?
+        let mut notes: Vec<Note> = stmt
+            .query_map(params.as_slice(), read_note)?
             .filter_map(|r| r.ok())
             .collect();
+        for note in &mut notes {
+            self.reveal_secret(note)?;
+        }
 
         Ok(notes)
     }
@@ -127,85 +158,53 @@ impl NoteStore {
 
         // Check for date filter prefixes: y:2024, year:2024, m:01/24, month:01/24
         let (date_filter, text_query) = Self::parse_date_filter(query);
+        let (journal_filter, text_query) = Self::parse_journal_filter(&text_query);
 
-        let (sql, params_vec): (String, Vec<Box<dyn rusqlite::ToSql>>) = match (&date_filter, text_query.is_empty()) {
-            (None, true) => {
-                // No filter, no text - show all
-                (
-                    "SELECT id, title, body, created_at FROM notes ORDER BY created_at DESC LIMIT ?".to_string(),
-                    vec![Box::new(limit as i64) as Box<dyn rusqlite::ToSql>],
-                )
-            }
-            (None, false) => {
-                // Text search only
-                let pattern = format!("%{}%", text_query.to_lowercase());
-                (
-                    "SELECT id, title, body, created_at FROM notes
-                     WHERE lower(title) LIKE ?1 OR lower(body) LIKE ?1
-                     ORDER BY created_at DESC LIMIT ?2".to_string(),
-                    vec![
-                        Box::new(pattern) as Box<dyn rusqlite::ToSql>,
-                        Box::new(limit as i64),
-                    ],
-                )
-            }
-            (Some((year, None)), true) => {
-                // Year filter only
-                let year_pattern = format!("{:04}-%", year);
-                (
-                    "SELECT id, title, body, created_at FROM notes
-                     WHERE created_at LIKE ?1
-                     ORDER BY created_at DESC LIMIT ?2".to_string(),
-                    vec![
-                        Box::new(year_pattern) as Box<dyn rusqlite::ToSql>,
-                        Box::new(limit as i64),
-                    ],
-                )
-            }
-            (Some((year, Some(month))), true) => {
-                // Year + month filter only
-                let month_pattern = format!("{:04}-{:02}-%", year, month);
-                (
-                    "SELECT id, title, body, created_at FROM notes
-                     WHERE created_at LIKE ?1
-                     ORDER BY created_at DESC LIMIT ?2".to_string(),
-                    vec![
-                        Box::new(month_pattern) as Box<dyn rusqlite::ToSql>,
-                        Box::new(limit as i64),
-                    ],
-                )
-            }
-            (Some((year, None)), false) => {
-                // Year filter + text search
-                let year_pattern = format!("{:04}-%", year);
-                let text_pattern = format!("%{}%", text_query.to_lowercase());
-                (
-                    "SELECT id, title, body, created_at FROM notes
-                     WHERE created_at LIKE ?1 AND (lower(title) LIKE ?2 OR lower(body) LIKE ?2)
-                     ORDER BY created_at DESC LIMIT ?3".to_string(),
-                    vec![
-                        Box::new(year_pattern) as Box<dyn rusqlite::ToSql>,
-                        Box::new(text_pattern),
-                        Box::new(limit as i64),
-                    ],
-                )
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        match date_filter {
+            Some((year, None)) => {
+                conditions.push("created_at LIKE ?");
+                params_vec.push(Box::new(format!("{:04}-%", year)));
             }
-            (Some((year, Some(month))), false) => {
-                // Year + month filter + text search
-                let month_pattern = format!("{:04}-{:02}-%", year, month);
-                let text_pattern = format!("%{}%", text_query.to_lowercase());
-                (
-                    "SELECT id, title, body, created_at FROM notes
-                     WHERE created_at LIKE ?1 AND (lower(title) LIKE ?2 OR lower(body) LIKE ?2)
-                     ORDER BY created_at DESC LIMIT ?3".to_string(),
-                    vec![
-                        Box::new(month_pattern) as Box<dyn rusqlite::ToSql>,
-                        Box::new(text_pattern),
-                        Box::new(limit as i64),
-                    ],
-                )
+            Some((year, Some(month))) => {
+                conditions.push("created_at LIKE ?");
+                params_vec.push(Box::new(format!("{:04}-{:02}-%", year, month)));
             }
+            None => {}
+        }
+
+        // Daily notes, optionally only those of a year, month or day
+        if let Some(prefix) = &journal_filter {
+            conditions.push("journal_date LIKE ?");
+            params_vec.push(Box::new(format!("{}%", prefix)));
+        }
+
+        if !text_query.is_empty() {
+            conditions.push(
+                "(lower(kiro_decrypt(title)) LIKE ? OR (encrypted = 0 AND lower(kiro_decrypt(body)) LIKE ?))",
+            );
+            let pattern = format!("%{}%", text_query.to_lowercase());
+            params_vec.push(Box::new(pattern.clone()));
+            params_vec.push(Box::new(pattern));
+        }
+
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let order = if journal_filter.is_some() {
+            "journal_date DESC"
+        } else {
+            "created_at DESC"
         };
+        let sql = format!(
+            "SELECT {} FROM notes {} ORDER BY {} LIMIT ?",
+            SEARCH_COLUMNS, filter, order
+        );
+        params_vec.push(Box::new(limit as i64));
 
         let mut stmt = self.conn.prepare(&sql)?;
         let param_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|b| b.as_ref()).collect();
@@ -221,6 +220,7 @@ impl NoteStore {
                     body_preview,
                     created_at: parse_datetime(&row.get::<_, String>(3)?),
                     word_count,
+                    encrypted: row.get(4)?,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -262,6 +262,27 @@ impl NoteStore {
         (date_filter, remaining_parts.join(" "))
     }
 
+    /// Pull a `journal:` filter out of the query: `journal:` alone matches
+    /// every daily note, `journal:2024`, `journal:2024-03` or
+    /// `journal:2024-03-18` those of a year, month or day. Returns the date
+    /// prefix to match and the remaining text.
+    fn parse_journal_filter(query: &str) -> (Option<String>, String) {
+        let mut remaining_parts = Vec::new();
+        let mut journal_filter = None;
+
+        for part in query.split_whitespace() {
+            if let Some(prefix) = part.to_lowercase().strip_prefix("journal:") {
+                if prefix.chars().all(|c| c.is_ascii_digit() || c == '-') {
+                    journal_filter = Some(prefix.to_string());
+                    continue;
+                }
+            }
+            remaining_parts.push(part);
+        }
+
+        (journal_filter, remaining_parts.join(" "))
+    }
+
     /// Parse MM/YY or MM/YYYY format
     fn parse_month_year(s: &str) -> Option<(u32, i32)> {
         let parts: Vec<&str> = s.split('/').collect();
@@ -292,35 +313,39 @@ impl NoteStore {
     pub fn create(&self, title: &str, body: &str) -> Result<i64> {
         let now = Utc::now().to_rfc3339();
         self.conn.execute(
-            "INSERT INTO notes (title, body, created_at, updated_at) VALUES (?, ?, ?, ?)",
+            "INSERT INTO notes (title, body, created_at, updated_at) VALUES (kiro_encrypt(?), kiro_encrypt(?), ?, ?)",
             params![title, body, now, now],
         )?;
-        Ok(self.conn.last_insert_rowid())
+        let id = self.conn.last_insert_rowid();
+        self.publish(StoreEvent::Created { id });
+        Ok(id)
     }
 
     pub fn update(&self, id: i64, body: &str) -> Result<()> {
         let now = Utc::now().to_rfc3339();
         let rows = self.conn.execute(
-            "UPDATE notes SET body = ?, updated_at = ? WHERE id = ?",
-            params![body, now, id],
+            "UPDATE notes SET body = kiro_encrypt(?), updated_at = ? WHERE id = ?",
+            params![self.body_value(id, body)?, now, id],
         )?;
 
         if rows == 0 {
             return Err(KiroError::NoteNotFound(id));
         }
+        self.publish(StoreEvent::Updated { id });
         Ok(())
     }
 
     pub fn update_title_and_body(&self, id: i64, title: &str, body: &str) -> Result<()> {
         let now = Utc::now().to_rfc3339();
         let rows = self.conn.execute(
-            "UPDATE notes SET title = ?, body = ?, updated_at = ? WHERE id = ?",
-            params![title, body, now, id],
+            "UPDATE notes SET title = kiro_encrypt(?), body = kiro_encrypt(?), updated_at = ? WHERE id = ?",
+            params![title, self.body_value(id, body)?, now, id],
         )?;
 
         if rows == 0 {
             return Err(KiroError::NoteNotFound(id));
         }
+        self.publish(StoreEvent::Updated { id });
         Ok(())
     }
 
@@ -329,6 +354,7 @@ impl NoteStore {
         if rows == 0 {
             return Err(KiroError::NoteNotFound(id));
         }
+        self.publish(StoreEvent::Deleted { id });
         Ok(())
     }
 
@@ -338,9 +364,18 @@ impl NoteStore {
         }
 
         let placeholders: String = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-        let sql = format!("DELETE FROM notes WHERE id IN ({})", placeholders);
+        let sql = format!("DELETE FROM notes WHERE id IN ({}) RETURNING id", placeholders);
         let params: Vec<&dyn rusqlite::ToSql> = ids.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
-        let rows = self.conn.execute(&sql, params.as_slice())?;
+        // Only the rows that existed, so subscribers never see unknown ids
+        let mut stmt = self.conn.prepare(&sql)?;
+        let deleted = stmt
+            .query_map(params.as_slice(), |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<i64>>>()?;
+
+        let rows = deleted.len();
+        if rows > 0 {
+            self.publish(StoreEvent::BulkDeleted { ids: deleted });
+        }
         Ok(rows)
     }
 
@@ -363,9 +398,16 @@ impl NoteStore {
         format!("{:x}", hasher.finalize())
     }
 
+    /// Import hash for an existing note, computing one if it has none
+    fn note_hash(note: &Note) -> String {
+        note.import_hash
+            .clone()
+            .unwrap_or_else(|| Self::compute_hash(&note.title, &note.body))
+    }
+
     fn hash_exists(&self, hash: &str) -> Result<bool> {
         let count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM notes WHERE import_hash = ?",
+            "SELECT COUNT(*) FROM notes WHERE kiro_decrypt(import_hash) = ?",
             [hash],
             |row| row.get(0),
         )?;
@@ -374,17 +416,16 @@ impl NoteStore {
 
     /// Check if any note exists with similar content (for duplicate detection)
     /// Uses both import_hash check and content similarity check
-    fn content_exists(&self, title: &str, body: &str) -> Result<bool> {
+    fn content_exists(&self, hash: &str, title: &str, body: &str) -> Result<bool> {
         // First check import hash for exact match
-        let hash = Self::compute_hash(title, body);
-        if self.hash_exists(&hash)? {
+        if self.hash_exists(hash)? {
             return Ok(true);
         }
 
         // Check for existing note with same body content (regardless of title)
         // This catches manually created notes that have the same content
         let count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM notes WHERE body = ?",
+            "SELECT COUNT(*) FROM notes WHERE kiro_decrypt(body) = ?",
             [body],
             |row| row.get(0),
         )?;
@@ -397,7 +438,7 @@ impl NoteStore {
         // Use char-based slicing for UTF-8 safety
         let body_prefix: String = body.chars().take(200).collect();
         let count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM notes WHERE title = ? AND body LIKE ?",
+            "SELECT COUNT(*) FROM notes WHERE kiro_decrypt(title) = ? AND kiro_decrypt(body) LIKE ?",
             params![title, format!("{}%", body_prefix)],
             |row| row.get(0),
         )?;
@@ -405,16 +446,43 @@ impl NoteStore {
         Ok(count > 0)
     }
 
-    pub fn import_file(&self, path: &Path) -> Result<(bool, Option<i64>)> {
+    /// Import hash for a file. Always uses the file stem so the hash doesn't
+    /// change with the title strategy.
+    fn import_hash(path: &Path, content: &str) -> String {
+        Self::compute_hash(&title::file_stem(path), content)
+    }
+
+    /// Check whether importing this file with `strategy` would be skipped
+    /// as a duplicate
+    pub fn file_exists(&self, path: &Path, strategy: &TitleStrategy) -> Result<bool> {
+        Ok(self.read_import(path, strategy)?.duplicate)
+    }
+
+    /// Read a file to import, shared by the scan preview and the import so
+    /// both see the same title and duplicate check
+    fn read_import(&self, path: &Path, strategy: &TitleStrategy) -> Result<ImportFile> {
         let content = std::fs::read_to_string(path)?;
-        let title = path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("(untitled)")
-            .to_string();
+        let title = strategy.title_for(path, &content);
+        let hash = Self::import_hash(path, &content);
+        let duplicate = self.content_exists(&hash, &title, &content)?;
+        Ok(ImportFile {
+            title,
+            content,
+            hash,
+            duplicate,
+        })
+    }
+
+    fn import_file(&self, path: &Path, strategy: &TitleStrategy) -> Result<(bool, Option<i64>)> {
+        let ImportFile {
+            title,
+            content,
+            hash,
+            duplicate,
+        } = self.read_import(path, strategy)?;
 
         // Check for duplicates against all existing notes
-        if self.content_exists(&title, &content)? {
+        if duplicate {
             return Ok((false, None)); // Skip duplicate
         }
 
@@ -426,23 +494,35 @@ impl NoteStore {
             .unwrap_or_else(Utc::now)
             .to_rfc3339();
 
-        let hash = Self::compute_hash(&title, &content);
         let now = Utc::now().to_rfc3339();
         self.conn.execute(
-            "INSERT INTO notes (title, body, created_at, updated_at, import_hash) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO notes (title, body, created_at, updated_at, import_hash) VALUES (kiro_encrypt(?), kiro_encrypt(?), ?, ?, kiro_encrypt(?))",
             params![title, content, file_date, now, hash],
         )?;
 
         Ok((true, Some(self.conn.last_insert_rowid())))
     }
 
-    pub fn import_files(&self, paths: &[PathBuf]) -> Result<(usize, usize, Vec<i64>)> {
+    pub fn import_files(&self, paths: &[PathBuf], strategy: &TitleStrategy) -> Result<(usize, usize, Vec<i64>)> {
         let mut imported = 0;
         let mut skipped = 0;
         let mut ids = Vec::new();
 
         for path in paths {
-            match self.import_file(path) {
+            // JSON exports from Kiro hold many notes
+            if let Some(document) = ExportDocument::read(path) {
+                match self.import_document(&document) {
+                    Ok((doc_imported, doc_skipped, doc_ids)) => {
+                        imported += doc_imported;
+                        skipped += doc_skipped;
+                        ids.extend(doc_ids);
+                    }
+                    Err(_) => skipped += 1,
+                }
+                continue;
+            }
+
+            match self.import_file(path, strategy) {
                 Ok((true, Some(id))) => {
                     imported += 1;
                     ids.push(id);
@@ -460,45 +540,134 @@ impl NoteStore {
             }
         }
 
+        if !ids.is_empty() {
+            self.publish(StoreEvent::Imported { ids: ids.clone() });
+        }
         Ok((imported, skipped, ids))
     }
 
-    pub fn export_notes(&self, note_ids: &HashSet<i64>, dir: &Path) -> Result<usize> {
-        std::fs::create_dir_all(dir)?;
+    /// Import every note from a JSON export, keeping its dates
+    fn import_document(&self, document: &ExportDocument) -> Result<(usize, usize, Vec<i64>)> {
+        let mut skipped = 0;
+        let mut ids = Vec::new();
 
-        let mut count = 0;
-        for id in note_ids {
-            if let Some(note) = self.get(*id)? {
-                let safe_title: String = note
-                    .title
-                    .chars()
-                    .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
-                    .take(50)
-                    .collect();
-                let safe_title = safe_title.trim().replace(' ', "-");
-                let filename = if safe_title.is_empty() {
-                    format!("note-{}.md", id)
-                } else {
-                    format!("note-{}-{}.md", id, safe_title)
-                };
-
-                let content = format!(
-                    "# {}\n\n_Created: {} | Updated: {}_\n\n{}",
+        for note in &document.notes {
+            let hash = Self::note_hash(note);
+
+            if self.content_exists(&hash, &note.title, &note.body)? {
+                skipped += 1;
+                continue;
+            }
+
+            self.conn.execute(
+                "INSERT INTO notes (title, body, created_at, updated_at, import_hash) VALUES (kiro_encrypt(?), kiro_encrypt(?), ?, ?, kiro_encrypt(?))",
+                params![
                     note.title,
-                    note.created_at.format("%Y-%m-%d %H:%M"),
-                    note.updated_at.format("%Y-%m-%d %H:%M"),
-                    note.body
-                );
+                    note.body,
+                    note.created_at.to_rfc3339(),
+                    note.updated_at.to_rfc3339(),
+                    hash
+                ],
+            )?;
+            ids.push(self.conn.last_insert_rowid());
+        }
+
+        Ok((ids.len(), skipped, ids))
+    }
+
+    /// Notes to export, leaving out secret notes that are still locked
+    fn exportable_notes(&self, note_ids: &HashSet<i64>) -> Result<Vec<Note>> {
+        let ids: Vec<i64> = note_ids.iter().copied().collect();
+        let mut notes = self.get_many(&ids)?;
+        notes.retain(|note| !note.locked);
+        Ok(notes)
+    }
+
+    /// Export notes into `dir`. Returns the number exported and the path
+    /// written: the directory itself, or the single JSON/zip file.
+    pub fn export_notes(&self, note_ids: &HashSet<i64>, dir: &Path, options: &ExportOptions) -> Result<(usize, PathBuf)> {
+        let renderer = NoteRenderer::new(options)?;
+        std::fs::create_dir_all(dir)?;
+
+        let timestamp = Utc::now().format("%Y%m%d-%H%M%S");
+
+        if options.format == ExportFormat::Site {
+            let notes = self.exportable_notes(note_ids)?;
+            let name = format!("kiro-site-{}", timestamp);
+            if !options.archive {
+                let path = site::write_site(dir, &name, &notes)?;
+                return Ok((notes.len(), path));
+            }
+
+            // Build the site in a scratch folder next to the zip. Creating it
+            // fails if the name is taken, so nothing else's files are zipped
+            // or removed.
+            let scratch = dir.join(format!(".{}.partial", name));
+            std::fs::create_dir(&scratch)?;
+            let zip_path = dir.join(format!("{}.zip", name));
+            let zipped = site::write_site(&scratch, &name, &notes)
+                .and_then(|path| archive::write_dir_archive(&path, &zip_path));
+            let _ = std::fs::remove_dir_all(&scratch);
+            zipped?;
+            return Ok((notes.len(), zip_path));
+        }
+        let single_document = options.format == ExportFormat::Json && !renderer.has_template();
 
-                std::fs::write(dir.join(&filename), content)?;
-                count += 1;
+        if options.archive || single_document {
+            let document = ExportDocument::new(self.exportable_notes(note_ids)?);
+            let count = document.notes.len();
+
+            if options.archive {
+                let path = dir.join(format!("kiro-export-{}.zip", timestamp));
+                archive::write_archive(&path, &document, &renderer, Self::note_hash)?;
+                return Ok((count, path));
             }
+
+            let contents = serde_json::to_string_pretty(&document)
+                .map_err(|e| KiroError::ExportFailed(e.to_string()))?;
+            let path = dir.join(format!("kiro-export-{}.json", timestamp));
+            std::fs::write(&path, contents)?;
+
+            return Ok((count, path));
+        }
+
+        let mut count = 0;
+        let mut used = HashSet::new();
+        for note in self.exportable_notes(note_ids)? {
+            let filename = export::unique_filename(&mut used, renderer.filename(&note)?, note.id);
+            let content = renderer.render(&note)?;
+
+            std::fs::write(dir.join(&filename), content)?;
+            count += 1;
         }
 
-        Ok(count)
+        Ok((count, dir.to_path_buf()))
     }
 }
 
+fn read_note(row: &rusqlite::Row) -> rusqlite::Result<Note> {
+    Ok(Note {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        body: row.get(2)?,
+        created_at: parse_datetime(&row.get::<_, String>(3)?),
+        updated_at: parse_datetime(&row.get::<_, String>(4)?),
+        import_hash: row.get(5)?,
+        encrypted: row.get(6)?,
+        locked: false,
+        journal_date: row.get::<_, Option<String>>(7)?.and_then(|d| d.parse().ok()),
+    })
+}
+
+/// A file read for import
+struct ImportFile {
+    title: String,
+    content: String,
+    hash: String,
+    /// Whether an existing note already holds it
+    duplicate: bool,
+}
+
 fn parse_datetime(s: &str) -> DateTime<Utc> {
     DateTime::parse_from_rfc3339(s)
         .map(|dt| dt.with_timezone(&Utc))
@@ -518,3 +687,34 @@ impl<T> OptionalExt<T> for std::result::Result<T, rusqlite::Error> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_and_import_agree_on_duplicates() {
+        let dir = std::env::temp_dir().join(format!("kiro-import-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = NoteStore::open(&dir.join("notes.db"), &BackupSettings::default()).unwrap();
+
+        // An edited copy of the file, titled by its heading
+        let file = dir.join("2026-10-18.md");
+        let files = [file.clone()];
+        std::fs::write(&file, "# Standup\nShipped the export dialog.").unwrap();
+        store.create("Standup", "# Standup\nShipped the export dialog.\nBlocked on review.").unwrap();
+
+        let heading = TitleStrategy::FirstHeading;
+        assert!(store.file_exists(&file, &heading).unwrap());
+        assert_eq!(store.import_files(&files, &heading).unwrap().0, 0);
+
+        let stem = TitleStrategy::FileStem;
+        assert!(!store.file_exists(&file, &stem).unwrap());
+        assert_eq!(store.import_files(&files, &stem).unwrap().0, 1);
+        assert!(store.file_exists(&file, &stem).unwrap());
+
+        drop(store);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
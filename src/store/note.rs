@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Compact result for search listings
@@ -9,6 +9,9 @@ pub struct SearchResult {
     pub body_preview: String,
     pub created_at: DateTime<Utc>,
     pub word_count: usize,
+    /// Secret note; the preview is left empty
+    #[serde(default)]
+    pub encrypted: bool,
 }
 
 impl SearchResult {
@@ -62,6 +65,15 @@ pub struct Note {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub import_hash: Option<String>,
+    /// Body is encrypted with the note's own passphrase
+    #[serde(default)]
+    pub encrypted: bool,
+    /// Secret note that hasn't been unlocked; the body is empty
+    #[serde(default)]
+    pub locked: bool,
+    /// Day of a daily note
+    #[serde(default)]
+    pub journal_date: Option<NaiveDate>,
 }
 
 impl Note {
@@ -74,6 +86,9 @@ impl Note {
             created_at: now,
             updated_at: now,
             import_hash: None,
+            encrypted: false,
+            locked: false,
+            journal_date: None,
         }
     }
 
@@ -0,0 +1,221 @@
+//! Templates for new notes, e.g. for standups or meeting notes.
+//!
+//! A note template is a `.tmpl` file in ~/.config/kiro/note-templates in the
+//! template language of `crate::template`. An optional header sets the
+//! title:
+//!
+//! ```text
+//! ---
+//! title: Standup {{date}}
+//! ---
+//! ## {{weekday}}
+//! Project: {{project}}
+//! - {{cursor}}
+//! ```
+//!
+//! `date`, `time`, `weekday` and `cursor` are filled in by Kiro; any other
+//! variable is asked for when the note is created. Without a header the
+//! title is the `title` variable. A `---` block with keys other than
+//! `title`, such as YAML front matter, isn't a header and stays in the body.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::error::{KiroError, Result};
+use crate::template::{split_header, Context, Template, Value};
+
+/// File extension of note templates, the same as export templates
+const TEMPLATE_EXTENSION: &str = "tmpl";
+
+/// Variables Kiro fills in itself
+pub const BUILTIN_VARIABLES: &[&str] = &["date", "time", "weekday", "cursor"];
+
+/// Stands in for `{{cursor}}` while rendering, then is removed
+const CURSOR_MARKER: char = '\u{0}';
+
+#[derive(Debug, Clone)]
+pub struct NoteTemplate {
+    title: Template,
+    body: Template,
+}
+
+/// A note template with the variables it asks for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteTemplateInfo {
+    pub name: String,
+    pub prompts: Vec<String>,
+}
+
+/// Title and body of a note made from a template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedNote {
+    pub title: String,
+    pub body: String,
+    /// Where `{{cursor}}` was in the body, in UTF-16 code units as used by
+    /// the editor's text area
+    pub cursor: Option<usize>,
+}
+
+impl NoteTemplate {
+    /// Note templates live in ~/.config/kiro/note-templates/<name>.tmpl
+    pub fn dir() -> Option<PathBuf> {
+        Config::config_dir().map(|p| p.join("note-templates"))
+    }
+
+    fn path(name: &str) -> Result<PathBuf> {
+        if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+            return Err(KiroError::InvalidTemplate(format!("invalid template name '{}'", name)));
+        }
+        let dir = Self::dir().ok_or_else(|| KiroError::InvalidPath("No config directory".to_string()))?;
+        Ok(dir.join(format!("{}.{}", name, TEMPLATE_EXTENSION)))
+    }
+
+    pub fn parse(source: &str) -> Result<Self> {
+        let (header, body) = split_header(source, &["title"]);
+        let title = header
+            .iter()
+            .rev()
+            .find(|(key, _)| *key == "title")
+            .map_or("{{title}}", |(_, value)| value);
+
+        Ok(Self {
+            title: Template::parse(title)?,
+            body: Template::parse(body)?,
+        })
+    }
+
+    /// The template file's text, for editing
+    pub fn source(name: &str) -> Result<String> {
+        fs::read_to_string(Self::path(name)?)
+            .map_err(|_| KiroError::InvalidTemplate(format!("template '{}' not found", name)))
+    }
+
+    pub fn load(name: &str) -> Result<Self> {
+        Self::parse(&Self::source(name)?)
+    }
+
+    /// Write a template, replacing one with the same name. The source must
+    /// parse.
+    pub fn save(name: &str, source: &str) -> Result<()> {
+        Self::parse(source)?;
+        let path = Self::path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, source)?;
+        Ok(())
+    }
+
+    pub fn delete(name: &str) -> Result<()> {
+        let path = Self::path(name)?;
+        if !path.is_file() {
+            return Err(KiroError::InvalidTemplate(format!("template '{}' not found", name)));
+        }
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    /// Every template that parses, sorted by name. Files that don't are
+    /// skipped and logged.
+    pub fn list() -> Vec<NoteTemplateInfo> {
+        let Some(dir) = Self::dir() else {
+            return Vec::new();
+        };
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut templates: Vec<NoteTemplateInfo> = read_dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(TEMPLATE_EXTENSION))
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_str()?.to_string();
+                let template = fs::read_to_string(&path)
+                    .map_err(KiroError::from)
+                    .and_then(|source| Self::parse(&source))
+                    .map_err(|e| eprintln!("Skipping note template {}: {}", path.display(), e))
+                    .ok()?;
+                Some(NoteTemplateInfo {
+                    name,
+                    prompts: template.prompts(),
+                })
+            })
+            .collect();
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        templates
+    }
+
+    /// Variables the user is asked for: all but `BUILTIN_VARIABLES`
+    pub fn prompts(&self) -> Vec<String> {
+        let mut prompts = self.title.variables();
+        for name in self.body.variables() {
+            if !prompts.contains(&name) {
+                prompts.push(name);
+            }
+        }
+        prompts.retain(|name| !BUILTIN_VARIABLES.contains(&name.as_str()));
+        prompts
+    }
+
+    /// Fill in the template with the answers to its prompts, dated `now`
+    /// in local time
+    pub fn render(&self, vars: &HashMap<String, String>, now: NaiveDateTime) -> Result<RenderedNote> {
+        let mut context: Context = vars
+            .iter()
+            .map(|(name, value)| (name.clone(), Value::Text(value.clone())))
+            .collect();
+        context.insert("date".to_string(), now.format("%Y-%m-%d").to_string().into());
+        context.insert("time".to_string(), now.format("%H:%M").to_string().into());
+        context.insert("weekday".to_string(), now.format("%A").to_string().into());
+        context.insert("cursor".to_string(), CURSOR_MARKER.to_string().into());
+
+        let title = self.title.render(&context)?.replace(CURSOR_MARKER, "");
+        let body = self.body.render(&context)?;
+        let cursor = body.find(CURSOR_MARKER).map(|at| body[..at].encode_utf16().count());
+
+        Ok(RenderedNote {
+            title: title.trim().to_string(),
+            body: body.replace(CURSOR_MARKER, ""),
+            cursor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn render(source: &str) -> RenderedNote {
+        let now = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        let vars = HashMap::from([("title".to_string(), "Sprint 12".to_string())]);
+        NoteTemplate::parse(source).unwrap().render(&vars, now).unwrap()
+    }
+
+    #[test]
+    fn title_header() {
+        let note = render("---\r\ntitle: Standup {{date}}\r\n---\r\n## {{weekday}}\r\n- {{cursor}}");
+        assert_eq!(note.title, "Standup 2026-10-18");
+        assert_eq!(note.body, "## Sunday\r\n- ");
+        assert_eq!(note.cursor, Some(13));
+    }
+
+    #[test]
+    fn front_matter_stays_in_the_body() {
+        let source = "---\ntitle: {{title}}\ntags: [meeting]\n---\nNotes";
+        let note = render(source);
+        assert_eq!(note.title, "Sprint 12");
+        assert_eq!(note.body, "---\ntitle: Sprint 12\ntags: [meeting]\n---\nNotes");
+
+        // After a title header, front matter is part of the body too
+        let note = render("---\ntitle: Retro\n---\n---\ntags: [meeting]\n---\nNotes");
+        assert_eq!(note.title, "Retro");
+        assert_eq!(note.body, "---\ntags: [meeting]\n---\nNotes");
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use rusqlite::params;
+use rusqlite::types::Value;
+
+use super::crypto::{self, Cipher, SALT_LEN};
+use super::{Note, NoteStore, OptionalExt, StoreEvent};
+use crate::error::{KiroError, Result};
+
+/// Prefix of a secret note body, followed by the salt, nonce and ciphertext
+const SECRET_MAGIC: &[u8] = b"KRS1";
+
+/// Key for one secret note, kept in memory while the note is unlocked
+pub(super) struct SecretKey {
+    salt: [u8; SALT_LEN],
+    cipher: Cipher,
+}
+
+impl SecretKey {
+    fn new(passphrase: &str) -> Result<Self> {
+        Self::with_salt(passphrase, crypto::new_salt())
+    }
+
+    fn with_salt(passphrase: &str, salt: [u8; SALT_LEN]) -> Result<Self> {
+        Ok(Self {
+            salt,
+            cipher: Cipher::derive(passphrase, &salt)?,
+        })
+    }
+
+    /// Derive the key for a sealed body from its passphrase
+    fn for_sealed(passphrase: &str, data: &[u8]) -> Result<Self> {
+        let (salt, _) = split_sealed(data)?;
+        let key = Self::with_salt(passphrase, salt)?;
+        // Check the passphrase before keeping the key
+        key.unseal(data)?;
+        Ok(key)
+    }
+
+    fn seal(&self, body: &str) -> Result<Vec<u8>> {
+        let mut out = SECRET_MAGIC.to_vec();
+        out.extend_from_slice(&self.salt);
+        out.extend(self.cipher.encrypt(body)?);
+        Ok(out)
+    }
+
+    fn unseal(&self, data: &[u8]) -> Result<String> {
+        let (_, ciphertext) = split_sealed(data)?;
+        self.cipher.decrypt(ciphertext)
+    }
+}
+
+/// Split a sealed body into its salt and encrypted part
+fn split_sealed(data: &[u8]) -> Result<([u8; SALT_LEN], &[u8])> {
+    let data = data
+        .strip_prefix(SECRET_MAGIC)
+        .filter(|d| d.len() >= SALT_LEN)
+        .ok_or_else(|| KiroError::Encryption("malformed secret note".into()))?;
+    let (salt, rest) = data.split_at(SALT_LEN);
+
+    let mut fixed = [0u8; SALT_LEN];
+    fixed.copy_from_slice(salt);
+    Ok((fixed, rest))
+}
+
+/// Keys of the secret notes unlocked this session, by note id
+pub(super) type SecretKeys = Mutex<HashMap<i64, SecretKey>>;
+
+impl NoteStore {
+    /// Encrypt a note's body with its own passphrase. The note stays
+    /// unlocked until `lock_note` or `lock_notes`. `secure_delete` zeroes
+    /// the old body in the database file, and the import hash is cleared
+    /// because it is derived from the body.
+    pub fn set_note_secret(&self, id: i64, passphrase: &str) -> Result<()> {
+        if passphrase.is_empty() {
+            return Err(KiroError::validation("passphrase", "must not be empty"));
+        }
+        if self.is_secret(id)? {
+            return Err(KiroError::Encryption(format!("note {} is already secret", id)));
+        }
+
+        let note = self.get(id)?.ok_or(KiroError::NoteNotFound(id))?;
+        let key = SecretKey::new(passphrase)?;
+        self.conn.execute(
+            "UPDATE notes SET body = ?, encrypted = 1, import_hash = NULL WHERE id = ?",
+            params![key.seal(&note.body)?, id],
+        )?;
+
+        self.secret_keys().insert(id, key);
+        self.publish(StoreEvent::Updated { id });
+        Ok(())
+    }
+
+    /// Decrypt a secret note's body permanently
+    pub fn clear_note_secret(&self, id: i64, passphrase: &str) -> Result<()> {
+        let note = self.unlock_note(id, passphrase)?;
+        self.conn.execute(
+            "UPDATE notes SET body = kiro_encrypt(?), encrypted = 0 WHERE id = ?",
+            params![note.body, id],
+        )?;
+
+        self.secret_keys().remove(&id);
+        self.publish(StoreEvent::Updated { id });
+        Ok(())
+    }
+
+    /// Unlock a secret note for this session and return it
+    pub fn unlock_note(&self, id: i64, passphrase: &str) -> Result<Note> {
+        let data = self
+            .secret_data(id)?
+            .ok_or_else(|| KiroError::Encryption(format!("note {} is not secret", id)))?;
+        let key = SecretKey::for_sealed(passphrase, &data)?;
+
+        self.secret_keys().insert(id, key);
+        self.get(id)?.ok_or(KiroError::NoteNotFound(id))
+    }
+
+    /// Forget the key of one secret note
+    pub fn lock_note(&self, id: i64) {
+        self.secret_keys().remove(&id);
+    }
+
+    /// Forget the keys of all secret notes
+    pub fn lock_secret_notes(&self) {
+        self.secret_keys().clear();
+    }
+
+    /// Whether any secret note is unlocked
+    pub fn has_unlocked_secrets(&self) -> bool {
+        !self.secret_keys().is_empty()
+    }
+
+    pub(super) fn is_secret(&self, id: i64) -> Result<bool> {
+        Ok(self
+            .conn
+            .query_row("SELECT encrypted FROM notes WHERE id = ?", [id], |row| row.get(0))
+            .optional()?
+            .unwrap_or(false))
+    }
+
+    /// Fill in the body of a secret note read with a placeholder, or mark
+    /// it locked if it hasn't been unlocked
+    pub(super) fn reveal_secret(&self, note: &mut Note) -> Result<()> {
+        if !note.encrypted {
+            return Ok(());
+        }
+
+        let body = match (self.secret_data(note.id)?, self.secret_keys().get(&note.id)) {
+            (Some(data), Some(key)) => Some(key.unseal(&data)?),
+            _ => None,
+        };
+
+        match body {
+            Some(body) => note.body = body,
+            None => note.locked = true,
+        }
+        Ok(())
+    }
+
+    /// Value to store as a note's body: sealed with the note's key if it
+    /// is secret, plain text otherwise
+    pub(super) fn body_value(&self, id: i64, body: &str) -> Result<Value> {
+        if !self.is_secret(id)? {
+            return Ok(Value::Text(body.to_string()));
+        }
+
+        let keys = self.secret_keys();
+        let key = keys.get(&id).ok_or(KiroError::NoteLocked(id))?;
+        Ok(Value::Blob(key.seal(body)?))
+    }
+
+    fn secret_data(&self, id: i64) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT body FROM notes WHERE id = ? AND encrypted = 1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn secret_keys(&self) -> std::sync::MutexGuard<'_, HashMap<i64, SecretKey>> {
+        self.secrets.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,343 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use pulldown_cmark::{html, CowStr, Event, LinkType, Tag, TagEnd, TextMergeStream};
+use regex::Regex;
+use serde::Serialize;
+
+use super::export::{drop_unsafe_links, escape_html, escape_raw_html, markdown_parser, note_filename};
+use super::note::Note;
+use crate::error::{KiroError, Result};
+
+/// Characters of plain text per note kept in the search index
+const SEARCH_TEXT_LEN: usize = 2000;
+
+const STYLE_CSS: &str = r#"body { max-width: 52rem; margin: 0 auto; padding: 1rem 1.5rem 3rem; font-family: system-ui, sans-serif; line-height: 1.6; color: #222; }
+a { color: #3b6fd4; }
+header { border-bottom: 1px solid #ddd; margin-bottom: 1.5rem; padding-bottom: 0.5rem; }
+header a { text-decoration: none; font-weight: 600; }
+.meta { color: #666; font-size: 0.875rem; }
+.note-list { list-style: none; padding: 0; }
+.note-list li { display: flex; gap: 1rem; padding: 0.25rem 0; }
+.note-list .date { color: #666; font-variant-numeric: tabular-nums; min-width: 6rem; }
+.missing-link { color: #a33; }
+#search { width: 100%; padding: 0.5rem; font-size: 1rem; margin-bottom: 1rem; box-sizing: border-box; }
+pre { background: #f4f4f4; padding: 0.75rem; overflow-x: auto; }
+"#;
+
+const SEARCH_JS: &str = r#"// Client-side search over search-index.json (or search-index.js when
+// opened from the file system, where fetch() is not allowed)
+(function () {
+  const input = document.getElementById('search');
+  const list = document.getElementById('notes');
+  if (!input || !list) return;
+
+  const loadIndex = window.KIRO_SEARCH_INDEX
+    ? Promise.resolve(window.KIRO_SEARCH_INDEX)
+    : fetch('search-index.json').then(r => r.json());
+
+  loadIndex.then(index => {
+    const byUrl = new Map(index.map(entry => [entry.url, entry]));
+
+    input.addEventListener('input', () => {
+      const terms = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+      list.querySelectorAll('li').forEach(item => {
+        const entry = byUrl.get(item.dataset.url);
+        const haystack = entry ? (entry.title + ' ' + entry.text).toLowerCase() : '';
+        item.hidden = !terms.every(term => haystack.includes(term));
+      });
+      document.querySelectorAll('.month').forEach(section => {
+        section.hidden = !section.querySelector('li:not([hidden])');
+      });
+    });
+  });
+})();
+"#;
+
+#[derive(Serialize)]
+struct SearchEntry<'a> {
+    id: i64,
+    title: &'a str,
+    url: &'a str,
+    date: String,
+    text: String,
+}
+
+/// Write a browsable static site for `notes` into a new folder in `dir`:
+/// a page per note, an index grouped by month and a search index. Notes
+/// have no tags or notebooks, so there are no pages for them. Returns the
+/// path of the site folder.
+pub fn write_site(dir: &Path, name: &str, notes: &[Note]) -> Result<PathBuf> {
+    let root = dir.join(name);
+    std::fs::create_dir_all(root.join("notes"))?;
+    std::fs::create_dir_all(root.join("assets"))?;
+
+    let mut notes: Vec<&Note> = notes.iter().collect();
+    notes.sort_by_key(|n| Reverse(n.created_at));
+
+    // Relative URLs from the site root, used to resolve links between notes
+    let urls: HashMap<i64, String> = notes
+        .iter()
+        .map(|n| (n.id, format!("notes/{}", note_filename(n, "html"))))
+        .collect();
+    let links = LinkResolver::new(&notes, &urls);
+
+    for note in &notes {
+        let body = links.render(&note.body);
+        let content = format!(
+            "<h1>{}</h1>\n<p class=\"meta\">Created: {} | Updated: {}</p>\n{}",
+            escape_html(&note.title),
+            note.created_at.format("%Y-%m-%d %H:%M"),
+            note.updated_at.format("%Y-%m-%d %H:%M"),
+            body
+        );
+        std::fs::write(root.join(&urls[&note.id]), page(&note.title, &content, "../"))?;
+    }
+
+    std::fs::write(root.join("index.html"), index_page(&notes, &urls))?;
+    std::fs::write(root.join("assets/style.css"), STYLE_CSS)?;
+    std::fs::write(root.join("assets/search.js"), SEARCH_JS)?;
+
+    let index: Vec<SearchEntry> = notes
+        .iter()
+        .map(|n| SearchEntry {
+            id: n.id,
+            title: &n.title,
+            url: &urls[&n.id],
+            date: n.created_at.format("%Y-%m-%d").to_string(),
+            text: n.body.chars().take(SEARCH_TEXT_LEN).collect(),
+        })
+        .collect();
+    let json = serde_json::to_string(&index).map_err(|e| KiroError::ExportFailed(e.to_string()))?;
+    std::fs::write(root.join("search-index.js"), format!("window.KIRO_SEARCH_INDEX = {};\n", json))?;
+    std::fs::write(root.join("search-index.json"), json)?;
+
+    Ok(root)
+}
+
+fn page(title: &str, content: &str, prefix: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>{title}</title>
+<link rel="stylesheet" href="{prefix}assets/style.css">
+</head>
+<body>
+<header><a href="{prefix}index.html">Notes</a></header>
+{content}
+</body>
+</html>
+"#,
+        title = escape_html(title),
+        prefix = prefix,
+        content = content,
+    )
+}
+
+/// Index of all notes, newest first, grouped by month
+fn index_page(notes: &[&Note], urls: &HashMap<i64, String>) -> String {
+    let mut content = String::from(
+        "<input id=\"search\" type=\"search\" placeholder=\"Search notes...\" autofocus>\n<div id=\"notes\">\n",
+    );
+
+    let mut current_month = String::new();
+    for note in notes {
+        let month = note.created_at.format("%B %Y").to_string();
+        if month != current_month {
+            if !current_month.is_empty() {
+                content.push_str("</ul></section>\n");
+            }
+            content.push_str(&format!("<section class=\"month\"><h2>{}</h2><ul class=\"note-list\">\n", month));
+            current_month = month;
+        }
+
+        let url = &urls[&note.id];
+        content.push_str(&format!(
+            "<li data-url=\"{url}\"><span class=\"date\">{date}</span><a href=\"{url}\">{title}</a></li>\n",
+            url = escape_html(url),
+            date = note.created_at.format("%Y-%m-%d"),
+            title = escape_html(&note.title),
+        ));
+    }
+    if !current_month.is_empty() {
+        content.push_str("</ul></section>\n");
+    }
+    content.push_str("</div>\n<script src=\"search-index.js\"></script>\n<script src=\"assets/search.js\"></script>\n");
+
+    page("Notes", &content, "")
+}
+
+/// Rewrites links between notes to relative URLs.
+///
+/// Supports `[[Title]]` and `[[Title|label]]` wiki links, and Markdown links
+/// to `note:<id>`. Links to notes outside the export are marked as missing.
+struct LinkResolver<'a> {
+    by_title: HashMap<String, &'a str>,
+    urls: &'a HashMap<i64, String>,
+    wiki_link: Regex,
+}
+
+impl<'a> LinkResolver<'a> {
+    fn new(notes: &[&Note], urls: &'a HashMap<i64, String>) -> Self {
+        // Newest note wins if two share a title
+        let mut by_title = HashMap::new();
+        for note in notes.iter().rev() {
+            by_title.insert(note.title.to_lowercase(), urls[&note.id].as_str());
+        }
+
+        Self {
+            by_title,
+            urls,
+            wiki_link: Regex::new(r"\[\[([^\]|]+)(?:\|([^\]]+))?\]\]").expect("valid regex"),
+        }
+    }
+
+    /// Render a note body to HTML with links resolved. Note pages live in
+    /// `notes/`, so URLs are made relative to that folder. Wiki links are
+    /// only looked for in text, not in code, raw HTML is escaped and links
+    /// that could run script are reduced to their text.
+    fn render(&self, body: &str) -> String {
+        let mut in_code_block = false;
+        let mut in_link = false;
+        let events = TextMergeStream::new(markdown_parser(body))
+            .map(escape_raw_html)
+            .flat_map(|event| match event {
+                Event::Start(Tag::CodeBlock(_)) => {
+                    in_code_block = true;
+                    vec![event]
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    in_code_block = false;
+                    vec![event]
+                }
+                Event::Start(Tag::Link { link_type, dest_url, title, id }) => {
+                    in_link = true;
+                    let dest_url = match dest_url
+                        .strip_prefix("note:")
+                        .and_then(|id| id.parse::<i64>().ok())
+                        .and_then(|id| self.urls.get(&id))
+                    {
+                        Some(url) => CowStr::from(format!("../{}", url)),
+                        None => dest_url,
+                    };
+                    vec![Event::Start(Tag::Link { link_type, dest_url, title, id })]
+                }
+                Event::End(TagEnd::Link) => {
+                    in_link = false;
+                    vec![event]
+                }
+                Event::Text(text) if !in_code_block && !in_link => self.wiki_links(text),
+                event => vec![event],
+            });
+
+        let mut out = String::new();
+        html::push_html(&mut out, drop_unsafe_links(events));
+        out
+    }
+
+    /// Split text into plain text and `[[...]]` links
+    fn wiki_links<'e>(&self, text: CowStr<'e>) -> Vec<Event<'e>> {
+        let mut events = Vec::new();
+        let mut last = 0;
+        for caps in self.wiki_link.captures_iter(&text) {
+            let whole = caps.get(0).expect("match");
+            if whole.start() > last {
+                events.push(Event::Text(CowStr::from(text[last..whole.start()].to_string())));
+            }
+            last = whole.end();
+
+            let target = caps[1].trim();
+            let label = caps.get(2).map_or(target, |m| m.as_str().trim()).to_string();
+            match self.by_title.get(&target.to_lowercase()) {
+                Some(url) => {
+                    events.push(Event::Start(Tag::Link {
+                        link_type: LinkType::Inline,
+                        dest_url: CowStr::from(format!("../{}", url)),
+                        title: CowStr::from(""),
+                        id: CowStr::from(""),
+                    }));
+                    events.push(Event::Text(CowStr::from(label)));
+                    events.push(Event::End(TagEnd::Link));
+                }
+                None => events.push(Event::InlineHtml(CowStr::from(format!(
+                    "<span class=\"missing-link\">{}</span>",
+                    escape_html(&label)
+                )))),
+            }
+        }
+
+        if last == 0 {
+            return vec![Event::Text(text)];
+        }
+        if last < text.len() {
+            events.push(Event::Text(CowStr::from(text[last..].to_string())));
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(body: &str) -> String {
+        let notes = [Note::new(1, "Target".to_string(), String::new())];
+        let notes: Vec<&Note> = notes.iter().collect();
+        let urls = HashMap::from([(1, "notes/target.html".to_string())]);
+        LinkResolver::new(&notes, &urls).render(body)
+    }
+
+    #[test]
+    fn resolves_links_between_notes() {
+        assert!(render("[[Target]]").contains(r#"<a href="../notes/target.html">Target</a>"#));
+        assert!(render("[[target|the target]]").contains(r#"<a href="../notes/target.html">the target</a>"#));
+        assert!(render("[x](note:1)").contains(r#"<a href="../notes/target.html">x</a>"#));
+        assert!(render("[[Other]]").contains(r#"<span class="missing-link">Other</span>"#));
+        assert!(render("`[[Target]]`").contains("<code>[[Target]]</code>"));
+    }
+
+    #[test]
+    fn keeps_safe_urls() {
+        let urls = [
+            "https://example.com/a",
+            "http://example.com",
+            "mailto:me@example.com",
+            "other.html",
+            "../a/b.html#top",
+            "/x?y=z:1",
+            "#section",
+        ];
+        for url in urls {
+            let html = render(&format!("[x]({})", url));
+            assert!(html.contains("<a href="), "{}: {}", url, html);
+        }
+    }
+
+    #[test]
+    fn drops_script_urls() {
+        let bodies = [
+            "[x](javascript:alert(1))",
+            "[x](JavaScript:alert(1))",
+            "[x](<java\tscript:alert(1)>)",
+            "[x](javascript&#58;alert(1))",
+            "[x](data:text/html,<script>alert(1)</script>)",
+            "[x](vbscript:msgbox)",
+            "<javascript:alert(1)>",
+            "[x](note:99)",
+        ];
+        for body in bodies {
+            let html = render(body);
+            assert!(!html.contains("<a "), "{}: {}", body, html);
+            assert!(!html.contains("href"), "{}: {}", body, html);
+        }
+        assert_eq!(render("[click me](javascript:alert(1))"), "<p>click me</p>\n");
+
+        let html = render("![pic](javascript:alert(1)) [![pic](a.png)](javascript:x)");
+        assert!(!html.contains("javascript"), "{}", html);
+        assert!(html.contains(r#"<img src="a.png" alt="pic" />"#), "{}", html);
+    }
+}
@@ -0,0 +1,157 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Longest title derived from file content, in characters
+const MAX_TITLE_LEN: usize = 120;
+
+/// How an imported file's title is chosen
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum TitleStrategy {
+    /// File name without extension
+    #[default]
+    FileStem,
+    /// First non-empty line of the body
+    FirstLine,
+    /// First Markdown `#` heading
+    FirstHeading,
+    /// `title:` key in a leading `---` YAML front matter block
+    FrontMatter,
+    /// Template with `{stem}`, `{name}`, `{ext}`, `{parent_dir}` and
+    /// `{first_line}` placeholders
+    Template { template: String },
+}
+
+impl TitleStrategy {
+    /// Derive a title for a file, falling back to the file stem when the
+    /// strategy finds nothing
+    pub fn title_for(&self, path: &Path, content: &str) -> String {
+        let derived = match self {
+            TitleStrategy::FileStem => None,
+            TitleStrategy::FirstLine => first_line(content),
+            TitleStrategy::FirstHeading => first_heading(content),
+            TitleStrategy::FrontMatter => front_matter_title(content),
+            TitleStrategy::Template { template } => Some(expand_template(template, path, content)),
+        };
+
+        derived
+            .map(|t| clean_title(&t))
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| file_stem(path))
+    }
+}
+
+pub fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("(untitled)")
+        .to_string()
+}
+
+/// Collapse whitespace and cap the length
+fn clean_title(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_TITLE_LEN)
+        .collect()
+}
+
+/// Split off a leading `---` front matter block, returning (front matter, rest)
+fn split_front_matter(content: &str) -> (Option<&str>, &str) {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+
+    (None, content)
+}
+
+fn first_line(content: &str) -> Option<String> {
+    let (_, body) = split_front_matter(content);
+    body.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(String::from)
+}
+
+fn first_heading(content: &str) -> Option<String> {
+    let (_, body) = split_front_matter(content);
+    let mut in_code_block = false;
+
+    for line in body.lines() {
+        let line = line.trim_start();
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let hashes = line.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&hashes) {
+            let text = &line[hashes..];
+            if text.is_empty() || text.starts_with(char::is_whitespace) {
+                // Drop an optional closing sequence of #s
+                let text = text.trim().trim_end_matches('#').trim();
+                if !text.is_empty() {
+                    return Some(text.to_string());
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn front_matter_title(content: &str) -> Option<String> {
+    let (front_matter, _) = split_front_matter(content);
+
+    front_matter?.lines().find_map(|line| {
+        let value = line.strip_prefix("title:")?.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+        Some(value.to_string())
+    })
+}
+
+fn expand_template(template: &str, path: &Path, content: &str) -> String {
+    let name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("");
+    let ext = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("");
+    let parent_dir = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|s| s.to_str())
+        .unwrap_or("");
+
+    template
+        .replace("{stem}", &file_stem(path))
+        .replace("{name}", name)
+        .replace("{ext}", ext)
+        .replace("{parent_dir}", parent_dir)
+        .replace("{first_line}", &first_line(content).unwrap_or_default())
+}
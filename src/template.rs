@@ -0,0 +1,489 @@
+//! Small template language used for export templates.
+//!
+//! `{{name}}` inserts a variable, `{{name|filter|filter:"arg"}}` pipes it
+//! through filters, and `{{#if name}}...{{else}}...{{/if}}` renders a
+//! branch depending on whether the variable is set and non-empty
+//! (`{{#if !name}}` inverts the test). `\{{` emits a literal `{{`.
+//! Unknown variables render as empty text.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::{KiroError, Result};
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Text(String),
+    Date(DateTime<Utc>),
+    List(Vec<String>),
+    Number(i64),
+}
+
+impl Value {
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Text(s) => !s.is_empty(),
+            Value::List(items) => !items.is_empty(),
+            Value::Number(n) => *n != 0,
+            Value::Date(_) => true,
+        }
+    }
+
+    fn to_text(&self) -> String {
+        match self {
+            Value::Text(s) => s.clone(),
+            Value::Date(d) => d.to_rfc3339(),
+            Value::List(items) => items.join(", "),
+            Value::Number(n) => n.to_string(),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Text(s)
+    }
+}
+
+impl From<DateTime<Utc>> for Value {
+    fn from(d: DateTime<Utc>) -> Self {
+        Value::Date(d)
+    }
+}
+
+pub type Context = HashMap<String, Value>;
+
+#[derive(Debug, Clone)]
+struct Filter {
+    name: String,
+    arg: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Var { name: String, filters: Vec<Filter> },
+    If { name: String, negated: bool, then: Vec<Node>, otherwise: Vec<Node> },
+}
+
+/// An `{{#if}}` block still being parsed
+struct Block {
+    name: String,
+    negated: bool,
+    then: Vec<Node>,
+    otherwise: Vec<Node>,
+    in_else: bool,
+}
+
+impl Block {
+    fn nodes(&mut self) -> &mut Vec<Node> {
+        if self.in_else {
+            &mut self.otherwise
+        } else {
+            &mut self.then
+        }
+    }
+}
+
+const FILTERS: &[&str] = &["date", "upper", "lower", "slug", "default", "join", "truncate"];
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut stack: Vec<Block> = Vec::new();
+        let mut rest = source;
+
+        // Nodes go into the innermost open block, or the top level
+        macro_rules! current {
+            () => {
+                match stack.last_mut() {
+                    Some(block) => block.nodes(),
+                    None => &mut nodes,
+                }
+            };
+        }
+
+        while !rest.is_empty() {
+            let Some(start) = rest.find("{{") else {
+                current!().push(Node::Text(rest.to_string()));
+                break;
+            };
+
+            // Escaped `\{{` is literal text
+            if rest[..start].ends_with('\\') {
+                let text = format!("{}{{{{", &rest[..start - 1]);
+                current!().push(Node::Text(text));
+                rest = &rest[start + 2..];
+                continue;
+            }
+
+            if start > 0 {
+                current!().push(Node::Text(rest[..start].to_string()));
+            }
+
+            let after = &rest[start + 2..];
+            let end = after
+                .find("}}")
+                .ok_or_else(|| template_error("unclosed '{{'"))?;
+            let tag = after[..end].trim();
+            rest = &after[end + 2..];
+
+            if let Some(condition) = tag.strip_prefix("#if ") {
+                let condition = condition.trim();
+                let (negated, name) = match condition.strip_prefix('!') {
+                    Some(name) => (true, name.trim()),
+                    None => (false, condition),
+                };
+                stack.push(Block {
+                    name: name.to_string(),
+                    negated,
+                    then: Vec::new(),
+                    otherwise: Vec::new(),
+                    in_else: false,
+                });
+            } else if tag == "else" {
+                match stack.last_mut() {
+                    Some(block) if !block.in_else => block.in_else = true,
+                    _ => return Err(template_error("'{{else}}' without '{{#if}}'")),
+                }
+            } else if tag == "/if" {
+                let block = stack
+                    .pop()
+                    .ok_or_else(|| template_error("'{{/if}}' without '{{#if}}'"))?;
+                current!().push(Node::If {
+                    name: block.name,
+                    negated: block.negated,
+                    then: block.then,
+                    otherwise: block.otherwise,
+                });
+            } else {
+                let node = parse_var(tag)?;
+                current!().push(node);
+            }
+        }
+
+        if let Some(block) = stack.last() {
+            return Err(template_error(&format!("'{{{{#if {}}}}}' is never closed", block.name)));
+        }
+
+        Ok(Self { nodes })
+    }
+
+    pub fn render(&self, context: &Context) -> Result<String> {
+        let mut out = String::new();
+        render_nodes(&self.nodes, context, &mut out)?;
+        Ok(out)
+    }
+
+    /// Names of the variables the template uses, in order of first use
+    pub fn variables(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        collect_variables(&self.nodes, &mut names);
+        names
+    }
+}
+
+fn collect_variables(nodes: &[Node], names: &mut Vec<String>) {
+    for node in nodes {
+        let (name, children) = match node {
+            Node::Text(_) => continue,
+            Node::Var { name, .. } => (name, None),
+            Node::If { name, then, otherwise, .. } => (name, Some((then, otherwise))),
+        };
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+        if let Some((then, otherwise)) = children {
+            collect_variables(then, names);
+            collect_variables(otherwise, names);
+        }
+    }
+}
+
+/// Split a `---` header block of `key: value` lines off the start of a
+/// template file, returning the header lines and the rest. Lines may end
+/// in `\n` or `\r\n`. The block is only a header if every line sets one
+/// of `keys`; otherwise it is left in the template, so YAML front matter
+/// meant for the output isn't lost.
+pub fn split_header<'a>(source: &'a str, keys: &[&str]) -> (Vec<(&'a str, &'a str)>, &'a str) {
+    let Some(rest) = source.strip_prefix("---\n").or_else(|| source.strip_prefix("---\r\n")) else {
+        return (Vec::new(), source);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            let mut header = Vec::new();
+            for line in rest[..offset].lines().filter(|line| !line.trim().is_empty()) {
+                match line.split_once(':') {
+                    Some((key, value)) if keys.contains(&key.trim()) => header.push((key.trim(), value.trim())),
+                    _ => return (Vec::new(), source),
+                }
+            }
+            return (header, &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (Vec::new(), source)
+}
+
+fn template_error(message: &str) -> KiroError {
+    KiroError::InvalidTemplate(message.to_string())
+}
+
+/// Parse `name|filter|filter:"arg"`
+fn parse_var(tag: &str) -> Result<Node> {
+    let mut parts = split_unquoted(tag, '|').into_iter();
+    let name = parts.next().unwrap_or_default().trim().to_string();
+    if name.is_empty() {
+        return Err(template_error("empty '{{}}'"));
+    }
+
+    let mut filters = Vec::new();
+    for part in parts {
+        let (filter, arg) = match part.split_once(':') {
+            Some((filter, arg)) => {
+                let arg = arg.trim();
+                let arg = arg
+                    .strip_prefix('"')
+                    .and_then(|a| a.strip_suffix('"'))
+                    .unwrap_or(arg);
+                (filter.trim(), Some(arg.to_string()))
+            }
+            None => (part.trim(), None),
+        };
+
+        if !FILTERS.contains(&filter) {
+            return Err(template_error(&format!("unknown filter '{}'", filter)));
+        }
+        filters.push(Filter {
+            name: filter.to_string(),
+            arg,
+        });
+    }
+
+    Ok(Node::Var { name, filters })
+}
+
+/// Split on `sep` outside double quotes
+fn split_unquoted(s: &str, sep: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in s.chars() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        if c == sep && !quoted {
+            parts.push(std::mem::take(&mut current));
+        } else {
+            current.push(c);
+        }
+    }
+    parts.push(current);
+    parts
+}
+
+fn render_nodes(nodes: &[Node], context: &Context, out: &mut String) -> Result<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { name, filters } => {
+                let mut value = context.get(name).cloned();
+                for filter in filters {
+                    value = apply_filter(value, filter)?;
+                }
+                if let Some(value) = value {
+                    out.push_str(&value.to_text());
+                }
+            }
+            Node::If { name, negated, then, otherwise } => {
+                let truthy = context.get(name).is_some_and(Value::is_truthy);
+                let branch = if truthy != *negated { then } else { otherwise };
+                render_nodes(branch, context, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_filter(value: Option<Value>, filter: &Filter) -> Result<Option<Value>> {
+    let arg = filter.arg.as_deref();
+
+    if filter.name == "default" {
+        return Ok(match value {
+            Some(v) if v.is_truthy() => Some(v),
+            _ => Some(Value::Text(arg.unwrap_or_default().to_string())),
+        });
+    }
+
+    let Some(value) = value else {
+        return Ok(None);
+    };
+
+    let result = match filter.name.as_str() {
+        "date" => {
+            let date = match &value {
+                Value::Date(d) => Some(*d),
+                Value::Text(s) => DateTime::parse_from_rfc3339(s).ok().map(|d| d.with_timezone(&Utc)),
+                _ => None,
+            };
+            match date {
+                Some(d) => {
+                    let mut formatted = String::new();
+                    use std::fmt::Write;
+                    write!(formatted, "{}", d.format(arg.unwrap_or("%Y-%m-%d")))
+                        .map_err(|_| template_error(&format!("invalid date format '{}'", arg.unwrap_or(""))))?;
+                    Value::Text(formatted)
+                }
+                None => value,
+            }
+        }
+        "upper" => Value::Text(value.to_text().to_uppercase()),
+        "lower" => Value::Text(value.to_text().to_lowercase()),
+        "slug" => Value::Text(slugify(&value.to_text())),
+        "join" => match value {
+            Value::List(items) => Value::Text(items.join(arg.unwrap_or(", "))),
+            other => other,
+        },
+        "truncate" => {
+            let len: usize = arg
+                .and_then(|a| a.parse().ok())
+                .ok_or_else(|| template_error("truncate needs a length, e.g. truncate:80"))?;
+            Value::Text(value.to_text().chars().take(len).collect())
+        }
+        _ => value,
+    };
+
+    Ok(Some(result))
+}
+
+/// Lowercase, keep alphanumerics, collapse everything else to single dashes
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str, context: &[(&str, Value)]) -> Result<String> {
+        let context: Context = context.iter().map(|(k, v)| (k.to_string(), v.clone())).collect();
+        Template::parse(source)?.render(&context)
+    }
+
+    fn date() -> Value {
+        Value::Date(DateTime::parse_from_rfc3339("2024-03-18T09:30:00Z").unwrap().with_timezone(&Utc))
+    }
+
+    #[test]
+    fn variables_and_escapes() {
+        assert_eq!(render("Hi {{ name }}!", &[("name", "Ann".into())]).unwrap(), "Hi Ann!");
+        assert_eq!(render("[{{missing}}]", &[]).unwrap(), "[]");
+        assert_eq!(render("\\{{name}}", &[("name", "Ann".into())]).unwrap(), "{{name}}");
+    }
+
+    #[test]
+    fn filter_arguments() {
+        let list = Value::List(vec!["a".into(), "b".into()]);
+        assert_eq!(render(r#"{{items|join:" | "}}"#, &[("items", list.clone())]).unwrap(), "a | b");
+        assert_eq!(render("{{items|join}}", &[("items", list)]).unwrap(), "a, b");
+        assert_eq!(render(r#"{{when|date:"%d.%m.%Y"}}"#, &[("when", date())]).unwrap(), "18.03.2024");
+        assert_eq!(render("{{when|date}}", &[("when", date())]).unwrap(), "2024-03-18");
+        assert_eq!(render("{{title|truncate:3|upper}}", &[("title", "kiro notes".into())]).unwrap(), "KIR");
+        assert_eq!(render(r#"{{title|default:"Untitled"}}"#, &[("title", "".into())]).unwrap(), "Untitled");
+        assert_eq!(render("{{title|slug}}", &[("title", "Hello, World!".into())]).unwrap(), "hello-world");
+        assert!(render("{{title|truncate:many}}", &[("title", "x".into())]).is_err());
+        assert!(Template::parse("{{title|shout}}").is_err());
+    }
+
+    #[test]
+    fn date_filter_on_invalid_input() {
+        // Text that isn't a date passes through unchanged
+        assert_eq!(render("{{when|date}}", &[("when", "soon".into())]).unwrap(), "soon");
+        assert_eq!(
+            render("{{when|date}}", &[("when", "2024-03-18T09:30:00+02:00".into())]).unwrap(),
+            "2024-03-18"
+        );
+        assert_eq!(render("{{n|date}}", &[("n", Value::Number(3))]).unwrap(), "3");
+        assert!(render(r#"{{when|date:"%Q"}}"#, &[("when", date())]).is_err());
+    }
+
+    #[test]
+    fn nested_conditionals() {
+        let source = "{{#if a}}A{{#if !b}}-not b{{else}}-b{{/if}}{{else}}none{{/if}}";
+        assert_eq!(render(source, &[("a", "1".into())]).unwrap(), "A-not b");
+        assert_eq!(render(source, &[("a", "1".into()), ("b", "1".into())]).unwrap(), "A-b");
+        assert_eq!(render(source, &[("b", "1".into())]).unwrap(), "none");
+        assert_eq!(render("{{#if n}}yes{{/if}}", &[("n", Value::Number(0))]).unwrap(), "");
+
+        let template = Template::parse(source).unwrap();
+        assert_eq!(template.variables(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn unclosed_tags_are_errors() {
+        let sources = [
+            "{{title",
+            "{{#if a}}x",
+            "{{#if a}}{{#if b}}{{/if}}",
+            "{{/if}}",
+            "{{else}}",
+            "{{#if a}}{{else}}{{else}}{{/if}}",
+            "{{ }}",
+        ];
+        for source in sources {
+            assert!(Template::parse(source).is_err(), "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn headers() {
+        let (header, body) = split_header("---\nfilename: {{title}}.md\n---\n# {{title}}\n", &["filename"]);
+        assert_eq!(header, vec![("filename", "{{title}}.md")]);
+        assert_eq!(body, "# {{title}}\n");
+
+        let (header, body) = split_header("---\r\nfilename: a.md\r\n\r\n---\r\nbody", &["filename"]);
+        assert_eq!(header, vec![("filename", "a.md")]);
+        assert_eq!(body, "body");
+
+        let (header, body) = split_header("---\nfilename: a.md\n---", &["filename"]);
+        assert_eq!(header, vec![("filename", "a.md")]);
+        assert_eq!(body, "");
+
+        // Front matter for the output stays in the template
+        let front_matter = "---\nfilename: a.md\ntags: [notes]\n---\nbody";
+        assert_eq!(split_header(front_matter, &["filename"]), (Vec::new(), front_matter));
+        let list = "---\ntags:\n  - notes\n---\nbody";
+        assert_eq!(split_header(list, &["tags"]), (Vec::new(), list));
+        let unclosed = "---\nfilename: a.md\nbody";
+        assert_eq!(split_header(unclosed, &["filename"]), (Vec::new(), unclosed));
+    }
+
+    #[test]
+    fn slugify_text() {
+        assert_eq!(slugify("  Über Notes -- 2024! "), "über-notes-2024");
+        assert_eq!(slugify("***"), "");
+    }
+}
@@ -1,6 +1,28 @@
 // Tauri API wrapper
 
-const { invoke } = window.__TAURI__.core;
+const { invoke: tauriInvoke } = window.__TAURI__.core;
+
+// Commands reject with { code, message, ...fields }, e.g. { code: 'note_not_found', id: 42 }
+class ApiError extends Error {
+  constructor(error) {
+    super(error.message);
+    Object.assign(this, error);
+    this.name = 'ApiError';
+  }
+
+  // Messages are written for display, so show them without a prefix
+  toString() {
+    return this.message;
+  }
+}
+
+async function invoke(command, args) {
+  try {
+    return await tauriInvoke(command, args);
+  } catch (error) {
+    throw error && error.code ? new ApiError(error) : error;
+  }
+}
 
 const API = {
   // Search & Notes
@@ -16,6 +38,44 @@ const API = {
     return await invoke('create_note', { title, body });
   },
 
+  // Note templates
+  async listNoteTemplates() {
+    return await invoke('list_note_templates');
+  },
+
+  async getNoteTemplate(name) {
+    return await invoke('get_note_template', { name });
+  },
+
+  async saveNoteTemplate(name, source) {
+    return await invoke('save_note_template', { name, source });
+  },
+
+  async deleteNoteTemplate(name) {
+    return await invoke('delete_note_template', { name });
+  },
+
+  async createNoteFromTemplate(template, vars) {
+    return await invoke('create_note_from_template', { template, vars });
+  },
+
+  // Daily notes
+  async openDailyNote(date = null) {
+    return await invoke('open_daily_note', { date });
+  },
+
+  async adjacentDailyNote(date, forward) {
+    return await invoke('adjacent_daily_note', { date, forward });
+  },
+
+  async getJournalSettings() {
+    return await invoke('get_journal_settings');
+  },
+
+  async saveJournalSettings(settings) {
+    return await invoke('save_journal_settings', { settings });
+  },
+
   async updateNote(id, body) {
     return await invoke('update_note', { id, body });
   },
@@ -41,16 +101,141 @@ const API = {
     return await invoke('get_home_directories');
   },
 
-  async scanDirectories(dirs, pattern) {
-    return await invoke('scan_directories', { dirs, pattern });
+  async scanDirectories(dirs, patterns, options = null, titleStrategy = null) {
+    return await invoke('scan_directories', { dirs, patterns, options, titleStrategy });
+  },
+
+  async importFiles(paths, titleStrategy = null) {
+    return await invoke('import_files', { paths, titleStrategy });
+  },
+
+  async exportNotes(ids, options = {}) {
+    return await invoke('export_notes', { ids, options });
+  },
+
+  async listExportTemplates() {
+    return await invoke('list_export_templates');
+  },
+
+  // Backup & Restore
+  async backupDatabase(path) {
+    return await invoke('backup_database', { path });
+  },
+
+  async restoreDatabase(path) {
+    return await invoke('restore_database', { path });
+  },
+
+  async listBackups() {
+    return await invoke('list_backups');
+  },
+
+  async getBackupSettings() {
+    return await invoke('get_backup_settings');
+  },
+
+  async saveBackupSettings(settings) {
+    return await invoke('save_backup_settings', { settings });
+  },
+
+  // Encryption
+  async getLockState() {
+    return await invoke('get_lock_state');
+  },
+
+  async unlockNotes(passphrase) {
+    return await invoke('unlock_notes', { passphrase });
+  },
+
+  async lockNotes() {
+    return await invoke('lock_notes');
+  },
+
+  async setNoteSecret(id, passphrase) {
+    return await invoke('set_note_secret', { id, passphrase });
+  },
+
+  async clearNoteSecret(id, passphrase) {
+    return await invoke('clear_note_secret', { id, passphrase });
+  },
+
+  async unlockNote(id, passphrase) {
+    return await invoke('unlock_note', { id, passphrase });
+  },
+
+  async lockNote(id) {
+    return await invoke('lock_note', { id });
+  },
+
+  async enableEncryption(passphrase) {
+    return await invoke('enable_encryption', { passphrase });
+  },
+
+  async disableEncryption(passphrase) {
+    return await invoke('disable_encryption', { passphrase });
+  },
+
+  async getSecuritySettings() {
+    return await invoke('get_security_settings');
+  },
+
+  async saveSecuritySettings(settings) {
+    return await invoke('save_security_settings', { settings });
+  },
+
+  // HTTP API
+  async getApiSettings() {
+    return await invoke('get_api_settings');
   },
 
-  async importFiles(paths) {
-    return await invoke('import_files', { paths });
+  async saveApiSettings(settings) {
+    return await invoke('save_api_settings', { settings });
   },
 
-  async exportNotes(ids) {
-    return await invoke('export_notes', { ids });
+  // Vaults
+  async listVaults() {
+    return await invoke('list_vaults');
+  },
+
+  async createVault(name, path) {
+    return await invoke('create_vault', { name, path });
+  },
+
+  async openVault(name, path) {
+    return await invoke('open_vault', { name, path });
+  },
+
+  async switchVault(name) {
+    return await invoke('switch_vault', { name });
+  },
+
+  async closeVault(name) {
+    return await invoke('close_vault', { name });
+  },
+
+  // Keybindings
+  async getKeybindings() {
+    return await invoke('get_keybindings');
+  },
+
+  async setKeybinding(action, chords) {
+    return await invoke('set_keybinding', { action, chords });
+  },
+
+  async resetKeybindings(action = null) {
+    return await invoke('reset_keybindings', { action });
+  },
+
+  async listKeymapProfiles() {
+    return await invoke('list_keymap_profiles');
+  },
+
+  async exportKeymapProfile(name) {
+    return await invoke('export_keymap_profile', { name });
+  },
+
+  async importKeymapProfile(name) {
+    return await invoke('import_keymap_profile', { name });
   },
 
   // Config
@@ -58,6 +243,10 @@ const API = {
     return await invoke('get_config');
   },
 
+  async getConfigDiagnostics() {
+    return await invoke('get_config_diagnostics');
+  },
+
   async getScanDirectories() {
     return await invoke('get_scan_directories');
   },
@@ -66,6 +255,14 @@ const API = {
     return await invoke('set_scan_directories', { dirs });
   },
 
+  async getScanSettings() {
+    return await invoke('get_scan_settings');
+  },
+
+  async saveScanSettings(settings) {
+    return await invoke('save_scan_settings', { settings });
+  },
+
   // Theme settings
   async getThemeSettings() {
     return await invoke('get_theme_settings');
@@ -85,6 +282,31 @@ const API = {
 
   async saveThemeSettings(settings) {
     return await invoke('save_theme_settings', { settings });
+  },
+
+  async getActiveTheme() {
+    return await invoke('get_active_theme');
+  },
+
+  // User themes
+  async listThemes() {
+    return await invoke('list_themes');
+  },
+
+  async importTheme(path) {
+    return await invoke('import_theme', { path });
+  },
+
+  async exportTheme(id, path) {
+    return await invoke('export_theme', { id, path });
+  },
+
+  async createTheme(name) {
+    return await invoke('create_theme', { name });
+  },
+
+  async deleteTheme(id) {
+    return await invoke('delete_theme', { id });
   }
 };
 
@@ -185,18 +185,37 @@ document.addEventListener('DOMContentLoaded', async () => {
   WindowControls.init();
   ResizeHandle.init();
   Dialogs.init();
+  await Keyboard.init();
   await Settings.init();
+  await Security.init();
   Search.init();
   Editor.init();
   Import.init();
-  Keyboard.init();
+  Export.init();
+  await Vaults.init();
+  await Journal.init();
+  await Templates.init();
 
   // Focus search input
   document.getElementById('search-input').focus();
 
+  try {
+    showConfigDiagnostics(await API.getConfigDiagnostics());
+  } catch (error) {
+    console.error('Failed to get config diagnostics:', error);
+  }
+  window.__TAURI__.event.listen('config-invalid', (event) => {
+    showConfigDiagnostics(event.payload);
+  });
+
   console.log('Kiro GUI ready');
 });
 
+// Problems found in config.json, on startup or when it is edited
+function showConfigDiagnostics(diagnostics) {
+  diagnostics.forEach(d => showToast(escapeHtml('config.json: ' + d.message), 'warning', 10000));
+}
+
 // Handle unhandled errors
 window.addEventListener('error', (e) => {
   console.error('Unhandled error:', e.error);
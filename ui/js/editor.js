@@ -35,32 +35,48 @@ const Editor = {
     this.elements.body.addEventListener('input', () => this.markDirty());
 
     // Keyboard shortcuts in editor
-    this.elements.body.addEventListener('keydown', (e) => {
-      if (e.ctrlKey && e.key === 's') {
-        e.preventDefault();
-        this.save();
-      }
-    });
+    this.elements.body.addEventListener('keydown', (e) => this.handleKeyDown(e));
+    this.elements.title.addEventListener('keydown', (e) => this.handleKeyDown(e));
+  },
 
-    this.elements.title.addEventListener('keydown', (e) => {
-      if (e.ctrlKey && e.key === 's') {
+  handleKeyDown(e) {
+    switch (Keyboard.actionFor(e)) {
+      case 'save_note':
         e.preventDefault();
         this.save();
-      }
-    });
+        break;
+      case 'daily_note':
+        e.preventDefault();
+        Journal.open();
+        break;
+      case 'previous_day':
+      case 'next_day':
+        // Only daily notes have neighbours; elsewhere the keys move the caret
+        if (this.currentNote && this.currentNote.journal_date) {
+          e.preventDefault();
+          Journal.step(Keyboard.actionFor(e) === 'next_day');
+        }
+        break;
+    }
   },
 
-  async editNote(note) {
+  // Open a note for editing, with the caret at `cursor` if given
+  async editNote(note, cursor = null) {
     if (!note) return;
 
     // Load full note
     try {
-      const fullNote = await API.getNote(note.id);
+      let fullNote = await API.getNote(note.id);
       if (!fullNote) {
         showToast('Note not found', 'error');
         return;
       }
 
+      if (fullNote.locked) {
+        fullNote = await Security.unlockNote(fullNote);
+        if (!fullNote) return;
+      }
+
       this.currentNote = fullNote;
       this.isDirty = false;
       this.isActive = true;
@@ -76,6 +92,9 @@ const Editor = {
 
       // Focus body
       this.elements.body.focus();
+      if (cursor !== null) {
+        this.elements.body.setSelectionRange(cursor, cursor);
+      }
     } catch (error) {
       showToast('Failed to load note: ' + error, 'error');
     }
@@ -139,6 +158,11 @@ const Editor = {
       await Search.performSearch();
       await Search.updateTotalCount();
     } catch (error) {
+      // Notes auto-locked while editing: unlock and try again
+      if (error.code === 'locked') {
+        await Security.promptUnlock();
+        return this.save();
+      }
       showToast('Failed to save: ' + error, 'error');
     }
   },
@@ -0,0 +1,89 @@
+// Export dialog: format, export template, folder and zip archive for the
+// marked notes
+
+const Export = {
+  ids: [],
+  elements: {},
+
+  init() {
+    this.elements = {
+      dialog: document.getElementById('export-dialog'),
+      count: document.getElementById('export-count'),
+      format: document.getElementById('export-format'),
+      template: document.getElementById('export-template'),
+      destination: document.getElementById('export-destination'),
+      archive: document.getElementById('export-archive'),
+      runBtn: document.getElementById('export-run'),
+      cancelBtn: document.getElementById('export-cancel'),
+      closeBtn: document.getElementById('export-close')
+    };
+
+    this.elements.runBtn.addEventListener('click', () => this.run());
+    this.elements.cancelBtn.addEventListener('click', () => this.close());
+    this.elements.closeBtn.addEventListener('click', () => this.close());
+    this.elements.dialog.addEventListener('click', (e) => {
+      if (e.target === this.elements.dialog) this.close();
+    });
+    this.elements.template.addEventListener('change', () => this.updateFields());
+  },
+
+  isOpen() {
+    return this.elements.dialog.classList.contains('active');
+  },
+
+  async open(ids) {
+    this.ids = ids;
+    this.elements.count.textContent = `${ids.length} note(s) marked`;
+
+    let templates = [];
+    try {
+      templates = await API.listExportTemplates();
+    } catch (error) {
+      console.error('Failed to list export templates:', error);
+    }
+    const selected = this.elements.template.value;
+    this.elements.template.innerHTML = '<option value="">None</option>' + templates
+      .map(name => `<option value="${escapeHtml(name)}">${escapeHtml(name)}</option>`)
+      .join('');
+    this.elements.template.value = templates.includes(selected) ? selected : '';
+
+    this.updateFields();
+    this.elements.dialog.classList.add('active');
+    this.elements.format.focus();
+  },
+
+  close() {
+    this.elements.dialog.classList.remove('active');
+  },
+
+  // A template renders the notes itself, so the format only picks the
+  // file extension
+  updateFields() {
+    this.elements.format.querySelector('option[value="site"]').disabled = !!this.elements.template.value;
+    if (this.elements.template.value && this.elements.format.value === 'site') {
+      this.elements.format.value = 'markdown';
+    }
+  },
+
+  options() {
+    const destination = this.elements.destination.value.trim();
+    return {
+      format: this.elements.format.value,
+      template: this.elements.template.value || null,
+      destination: destination || null,
+      archive: this.elements.archive.checked
+    };
+  },
+
+  async run() {
+    try {
+      const result = await API.exportNotes(this.ids, this.options());
+      this.close();
+      showToast(result, 'success');
+    } catch (error) {
+      showToast('Export failed: ' + error, 'error');
+    }
+  }
+};
+
+window.Export = Export;
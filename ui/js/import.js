@@ -11,6 +11,7 @@ const Import = {
     directoryList: null,
     fileFilter: null,
     fileList: null,
+    titleStrategy: null,
     scanBtn: null,
     selectAllBtn: null,
     clearBtn: null,
@@ -23,6 +24,7 @@ const Import = {
     this.elements.directoryList = document.getElementById('directory-list');
     this.elements.fileFilter = document.getElementById('file-filter');
     this.elements.fileList = document.getElementById('file-list');
+    this.elements.titleStrategy = document.getElementById('import-title-strategy');
     this.elements.scanBtn = document.getElementById('scan-files');
     this.elements.selectAllBtn = document.getElementById('import-select-all');
     this.elements.clearBtn = document.getElementById('import-clear');
@@ -35,6 +37,11 @@ const Import = {
     this.elements.selectAllBtn.addEventListener('click', () => this.selectAllFiles());
     this.elements.clearBtn.addEventListener('click', () => this.clearFiles());
     this.elements.confirmBtn.addEventListener('click', () => this.importSelected());
+    // The title takes part in the duplicate check, so scanned files are
+    // checked again with the new strategy
+    this.elements.titleStrategy.addEventListener('change', () => {
+      if (this.files.length > 0) this.scanFiles();
+    });
 
     // Directory list click
     this.elements.directoryList.addEventListener('click', (e) => {
@@ -114,21 +121,39 @@ const Import = {
       return;
     }
 
-    const pattern = this.elements.fileFilter.value.trim() || '*.txt';
+    const patterns = this.splitPatterns(this.elements.fileFilter.value);
+    if (patterns.length === 0) {
+      patterns.push('*.txt');
+    }
 
     try {
       this.elements.scanBtn.disabled = true;
       this.elements.scanBtn.textContent = 'Scanning...';
 
       const dirs = Array.from(this.selectedDirs);
-      this.files = await API.scanDirectories(dirs, pattern);
+      const result = await API.scanDirectories(dirs, patterns, {
+        preview_lines: 3,
+        check_existing: true,
+      }, this.titleStrategy());
+      this.files = result.files;
       this.selectedFiles.clear();
 
-      // Auto-select all files
-      this.files.forEach(f => this.selectedFiles.add(f.path));
+      // Auto-select all files not already imported
+      this.files
+        .filter(f => !f.exists_in_store)
+        .forEach(f => this.selectedFiles.add(f.path));
 
       this.renderFiles();
-      showToast(`Found ${this.files.length} files`, 'success');
+
+      let message = `Found ${this.files.length} files`;
+      if (result.skipped.length > 0) {
+        message += ` (${result.skipped.length} skipped)`;
+      }
+      if (result.truncated) {
+        showToast(message + ', result limit reached', 'warning');
+      } else {
+        showToast(message, 'success');
+      }
     } catch (error) {
       showToast('Scan failed: ' + error, 'error');
     } finally {
@@ -137,6 +162,28 @@ const Import = {
     }
   },
 
+  // Split "*.txt, *.{md,org}" into patterns, keeping commas inside braces
+  splitPatterns(value) {
+    const patterns = [];
+    let current = '';
+    let depth = 0;
+
+    for (const c of value) {
+      if (c === '{') depth++;
+      if (c === '}' && depth > 0) depth--;
+
+      if (depth === 0 && (c === ',' || /\s/.test(c))) {
+        if (current) patterns.push(current);
+        current = '';
+      } else {
+        current += c;
+      }
+    }
+    if (current) patterns.push(current);
+
+    return patterns;
+  },
+
   renderFiles() {
     if (this.files.length === 0) {
       this.elements.fileList.innerHTML = `
@@ -149,10 +196,12 @@ const Import = {
 
     this.elements.fileList.innerHTML = this.files.map(file => {
       const isSelected = this.selectedFiles.has(file.path);
+      const preview = escapeHtml(file.preview.join('\n')).replace(/"/g, '&quot;');
       return `
-        <div class="file-item ${isSelected ? 'selected' : ''}" data-path="${file.path}">
+        <div class="file-item ${isSelected ? 'selected' : ''} ${file.exists_in_store ? 'existing' : ''}" data-path="${file.path}" title="${preview}">
           <input type="checkbox" class="file-checkbox" ${isSelected ? 'checked' : ''}>
           <span class="file-name">${escapeHtml(file.name)}</span>
+          ${file.exists_in_store ? '<span class="file-badge">imported</span>' : ''}
           <span class="file-size">${formatFileSize(file.size)}</span>
         </div>
       `;
@@ -178,6 +227,10 @@ const Import = {
     this.renderFiles();
   },
 
+  titleStrategy() {
+    return { strategy: this.elements.titleStrategy.value };
+  },
+
   async importSelected() {
     if (this.selectedFiles.size === 0) {
       showToast('No files selected', 'warning');
@@ -189,7 +242,7 @@ const Import = {
       this.elements.confirmBtn.textContent = 'Importing...';
 
       const paths = Array.from(this.selectedFiles);
-      const result = await API.importFiles(paths);
+      const result = await API.importFiles(paths, this.titleStrategy());
 
       showToast(`Imported ${result.imported} files (${result.skipped} skipped)`, 'success');
 
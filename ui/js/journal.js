@@ -0,0 +1,115 @@
+// Daily notes: open today's note, step between days, and the Daily Notes
+// settings in the Templates tab
+
+const Journal = {
+  settings: { title_format: '%Y-%m-%d', template: null },
+  elements: {},
+
+  async init() {
+    this.elements = {
+      titleFormat: document.getElementById('journal-title-format'),
+      template: document.getElementById('journal-template'),
+      saveBtn: document.getElementById('journal-save')
+    };
+
+    this.elements.saveBtn.addEventListener('click', () => this.saveSettings());
+
+    // config.json edited outside the app
+    window.__TAURI__.event.listen('config-changed', (event) => {
+      this.setSettings(event.payload.journal);
+    });
+
+    try {
+      this.setSettings(await API.getJournalSettings());
+    } catch (error) {
+      console.error('Failed to load journal settings:', error);
+    }
+  },
+
+  setSettings(settings) {
+    this.settings = settings;
+    this.elements.titleFormat.value = settings.title_format;
+    this.renderTemplateOptions();
+  },
+
+  // Called by Templates whenever the list of note templates changes
+  renderTemplateOptions() {
+    const names = Templates.templates.map(template => template.name);
+    const selected = this.settings.template || '';
+    if (selected && !names.includes(selected)) {
+      names.push(selected);
+    }
+    this.elements.template.innerHTML = '<option value="">No template</option>' + names
+      .map(name => `<option value="${escapeHtml(name)}">${escapeHtml(name)}</option>`)
+      .join('');
+    this.elements.template.value = selected;
+  },
+
+  async saveSettings() {
+    const settings = {
+      title_format: this.elements.titleFormat.value.trim(),
+      template: this.elements.template.value || null
+    };
+
+    try {
+      await API.saveJournalSettings(settings);
+      this.settings = settings;
+      showToast('Daily note settings saved', 'success');
+    } catch (error) {
+      showToast('Failed to save daily note settings: ' + error, 'error');
+    }
+  },
+
+  // Open the daily note for `date` (YYYY-MM-DD), today if not given,
+  // creating it if needed
+  async open(date = null) {
+    if (!(await this.leaveEditor())) return;
+
+    try {
+      const note = await API.openDailyNote(date);
+      await this.show(note);
+    } catch (error) {
+      showToast('Failed to open daily note: ' + error, 'error');
+    }
+  },
+
+  // Move from the daily note in the editor to the nearest earlier or later one
+  async step(forward) {
+    const date = Editor.currentNote && Editor.currentNote.journal_date;
+    if (!date) return;
+    if (!(await this.leaveEditor())) return;
+
+    try {
+      const note = await API.adjacentDailyNote(date, forward);
+      if (!note) {
+        showToast(forward ? 'No later daily note' : 'No earlier daily note', 'warning');
+        return;
+      }
+      await this.show(note);
+    } catch (error) {
+      showToast('Failed to open daily note: ' + error, 'error');
+    }
+  },
+
+  // Unsaved changes would be lost when the editor loads another note
+  async leaveEditor() {
+    if (!Editor.isActive || !Editor.isDirty) return true;
+    return await Dialogs.confirm(
+      'Unsaved Changes',
+      'You have unsaved changes. Are you sure you want to discard them?'
+    );
+  },
+
+  async show(note) {
+    await Search.performSearch();
+    await Search.updateTotalCount();
+
+    const index = Search.results.findIndex(r => r.id === note.id);
+    if (index >= 0) {
+      Search.selectIndex(index);
+    }
+    await Editor.editNote(note);
+  }
+};
+
+window.Journal = Journal;
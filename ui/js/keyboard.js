@@ -2,25 +2,148 @@
 
 const Keyboard = {
   focusMode: 'search', // 'search' | 'results'
+  keymap: {},          // action -> chords, from the backend
+  bindings: {},        // chord -> action
+  capture: null,       // set while the Shortcuts tab records a new chord
 
-  init() {
+  async init() {
     document.addEventListener('keydown', (e) => this.handleKeyDown(e));
+
+    await this.loadKeymap();
+    // config.json edited outside the app
+    window.__TAURI__.event.listen('config-changed', () => this.loadKeymap());
+  },
+
+  async loadKeymap() {
+    try {
+      this.setKeymap(await API.getKeybindings());
+    } catch (error) {
+      console.error('Failed to load keybindings:', error);
+    }
+  },
+
+  setKeymap(keymap) {
+    this.keymap = keymap;
+    this.bindings = {};
+    for (const [action, chords] of Object.entries(keymap)) {
+      chords.forEach(chord => { this.bindings[chord] = action; });
+    }
+    this.renderHelp();
+    Settings.renderShortcuts();
+  },
+
+  // The chord for a key event, in the form the backend stores: modifiers in
+  // Ctrl+Alt+Shift+Meta order, then the key. Shift is part of a character
+  // (G, ?), so it is only written out for named keys like Tab.
+  chordFor(e) {
+    if (['Control', 'Alt', 'Shift', 'Meta', 'Dead', 'Unidentified'].includes(e.key)) return null;
+
+    let key = e.key === ' ' ? 'Space' : e.key;
+    const isChar = [...key].length === 1;
+    if (isChar && (e.ctrlKey || e.altKey || e.metaKey) && key.toLowerCase() !== key.toUpperCase()) {
+      key = e.shiftKey ? key.toUpperCase() : key.toLowerCase();
+    }
+
+    const modifiers = [];
+    if (e.ctrlKey) modifiers.push('Ctrl');
+    if (e.altKey) modifiers.push('Alt');
+    if (e.shiftKey && !isChar) modifiers.push('Shift');
+    if (e.metaKey) modifiers.push('Meta');
+    return [...modifiers, key].join('+');
+  },
+
+  actionFor(e) {
+    const chord = this.chordFor(e);
+    return chord ? this.bindings[chord] : undefined;
+  },
+
+  // How a chord is shown in help and settings, e.g. Ctrl+Shift+D or ↓
+  formatChord(chord) {
+    const parts = chord === '+' ? ['+']
+      : chord.endsWith('++') ? [...chord.slice(0, -2).split('+'), '+']
+      : chord.split('+');
+    let key = parts.pop();
+    if (parts.length > 0 && key.length === 1) {
+      if (key !== key.toLowerCase()) parts.push('Shift');
+      key = key.toUpperCase();
+    }
+    const arrows = { ArrowUp: '↑', ArrowDown: '↓', ArrowLeft: '←', ArrowRight: '→' };
+    return [...parts, arrows[key] || key].join('+');
+  },
+
+  // Shortcuts in the help dialog show the current chords
+  renderHelp() {
+    document.querySelectorAll('.help-key[data-action]').forEach(el => {
+      const chords = this.keymap[el.dataset.action] || [];
+      el.textContent = chords.length > 0 ? chords.map(c => this.formatChord(c)).join(' / ') : 'Unbound';
+    });
+  },
+
+  // Call `callback` with the next chord pressed, or null if Escape cancels
+  captureChord(callback) {
+    this.capture = callback;
   },
 
   handleKeyDown(e) {
+    if (this.capture) {
+      const chord = this.chordFor(e);
+      if (!chord) return;
+      e.preventDefault();
+      e.stopPropagation();
+      const callback = this.capture;
+      this.capture = null;
+      callback(chord === 'Escape' ? null : chord);
+      return;
+    }
+
+    const action = this.actionFor(e);
+
+    // Passphrase dialog handles its own Enter; cancel closes it
+    if (Security.isUnlockOpen()) {
+      if (action === 'cancel') {
+        e.preventDefault();
+        Security.cancelPassphrase();
+      }
+      return;
+    }
+
+    // Template dialog: Enter creates the note, cancel closes it
+    if (Templates.isOpen()) {
+      if (action === 'cancel') {
+        e.preventDefault();
+        Templates.close();
+      } else if (e.key === 'Enter') {
+        e.preventDefault();
+        Templates.create();
+      }
+      return;
+    }
+
+    // Export dialog: Enter exports, cancel closes it
+    if (Export.isOpen()) {
+      if (action === 'cancel') {
+        e.preventDefault();
+        Export.close();
+      } else if (e.key === 'Enter') {
+        e.preventDefault();
+        Export.run();
+      }
+      return;
+    }
+
     // Don't handle if in editor (editor handles its own shortcuts)
     if (Editor.isActive) {
-      if (e.key === 'Escape') {
+      if (action === 'cancel') {
         e.preventDefault();
         Editor.handleEscape();
       }
-      // Let editor handle Ctrl+S itself to avoid double-save
+      // Let editor handle save itself to avoid double-save
       return;
     }
 
     // Check for settings panel
     if (Settings.isOpen) {
-      if (e.key === 'Escape') {
+      if (action === 'cancel') {
         e.preventDefault();
         Settings.close();
       }
@@ -29,7 +152,7 @@ const Keyboard = {
 
     // Check for active dialogs
     if (Dialogs.isOpen()) {
-      if (e.key === 'Escape') {
+      if (action === 'cancel') {
         e.preventDefault();
         Dialogs.closeAll();
       }
@@ -40,165 +163,106 @@ const Keyboard = {
       return;
     }
 
-    // Global shortcuts
-    if (e.ctrlKey && e.key === 'q') {
-      e.preventDefault();
-      const { invoke } = window.__TAURI__.core;
-      invoke('window_close');
+    // Characters typed into the search box are text, not shortcuts
+    if (!action || (this.isTyping() && this.typesText(e))) {
       return;
     }
 
-    // Ctrl+, to open settings
-    if (e.ctrlKey && e.key === ',') {
-      e.preventDefault();
-      Settings.toggle();
+    // Marking only applies with the results list focused
+    const resultsOnly = ['toggle_mark', 'mark_all', 'clear_marks'];
+    if (resultsOnly.includes(action) && this.focusMode !== 'results') {
       return;
     }
 
-    // F-keys
-    if (e.key === 'F2') {
-      e.preventDefault();
-      Import.open();
-      return;
-    }
-
-    if (e.key === 'F3') {
-      e.preventDefault();
-      Search.exportMarked();
-      return;
-    }
-
-    // Help
-    if (e.key === '?' && !this.isTyping()) {
-      e.preventDefault();
-      Dialogs.showHelp();
-      return;
-    }
-
-    // Tab to toggle focus
-    if (e.key === 'Tab' && !e.ctrlKey) {
-      e.preventDefault();
-      this.toggleFocus();
-      return;
-    }
+    e.preventDefault();
+    this.runAction(action);
+  },
 
-    // Escape to clear/cancel
-    if (e.key === 'Escape') {
-      e.preventDefault();
-      const searchInput = document.getElementById('search-input');
-      if (searchInput.value) {
-        searchInput.value = '';
-        Search.performSearch();
+  runAction(action) {
+    switch (action) {
+      case 'quit':
+        window.__TAURI__.core.invoke('window_close');
+        break;
+      case 'open_settings':
+        Settings.toggle();
+        break;
+      case 'import':
+        Import.open();
+        break;
+      case 'export_marked':
+        Search.exportMarked();
+        break;
+      case 'show_help':
+        Dialogs.showHelp();
+        break;
+      case 'toggle_focus':
+        this.toggleFocus();
+        break;
+      case 'cancel': {
+        // Clear the search and return to it
+        const searchInput = document.getElementById('search-input');
+        if (searchInput.value) {
+          searchInput.value = '';
+          Search.performSearch();
+        }
+        searchInput.focus();
+        this.focusMode = 'search';
+        break;
       }
-      searchInput.focus();
-      this.focusMode = 'search';
-      return;
-    }
-
-    // If focused on search input, only handle specific keys
-    if (this.focusMode === 'search' && this.isTyping()) {
-      if (e.key === 'Enter') {
-        e.preventDefault();
+      case 'open_note': {
         const note = Search.getSelectedNote();
         if (note) {
           Editor.editNote(note);
-        } else {
+        } else if (this.focusMode === 'search') {
           // Create new note with search text as title
           const title = document.getElementById('search-input').value.trim();
           if (title) {
             Search.createNote(title);
           }
         }
-        return;
+        break;
       }
-
-      if (e.key === 'ArrowDown') {
-        e.preventDefault();
+      case 'new_from_template':
+        Templates.open();
+        break;
+      case 'daily_note':
+        Journal.open();
+        break;
+      case 'move_down':
         this.focusMode = 'results';
         Search.moveSelection(1);
-        return;
-      }
-
-      if (e.key === 'ArrowUp') {
-        e.preventDefault();
+        break;
+      case 'move_up':
         this.focusMode = 'results';
         Search.moveSelection(-1);
-        return;
-      }
-
-      // Don't intercept other keys when typing
-      return;
-    }
-
-    // Results navigation
-    if (e.key === 'ArrowDown' || e.key === 'j') {
-      e.preventDefault();
-      Search.moveSelection(1);
-      return;
-    }
-
-    if (e.key === 'ArrowUp' || e.key === 'k') {
-      e.preventDefault();
-      Search.moveSelection(-1);
-      return;
-    }
-
-    if (e.key === 'g' && !e.ctrlKey) {
-      e.preventDefault();
-      Search.jumpToFirst();
-      return;
-    }
-
-    if (e.key === 'G') {
-      e.preventDefault();
-      Search.jumpToLast();
-      return;
-    }
-
-    if (e.ctrlKey && e.key === 'd') {
-      e.preventDefault();
-      Search.pageDown();
-      return;
-    }
-
-    if (e.ctrlKey && e.key === 'u') {
-      e.preventDefault();
-      Search.pageUp();
-      return;
-    }
-
-    // Actions
-    if (e.key === 'Enter') {
-      e.preventDefault();
-      const note = Search.getSelectedNote();
-      if (note) {
-        Editor.editNote(note);
-      }
-      return;
-    }
-
-    if (e.key === ' ' && this.focusMode === 'results') {
-      e.preventDefault();
-      Search.toggleCurrentMark();
-      return;
-    }
-
-    if (e.key === 'a' && this.focusMode === 'results') {
-      e.preventDefault();
-      Search.markAll();
-      return;
-    }
-
-    if (e.key === 'c' && !e.ctrlKey && this.focusMode === 'results') {
-      e.preventDefault();
-      Search.clearMarks();
-      return;
-    }
-
-    if (e.key === 'd' && !e.ctrlKey) {
-      e.preventDefault();
-      Search.deleteSelected();
-      return;
+        break;
+      case 'jump_first':
+        Search.jumpToFirst();
+        break;
+      case 'jump_last':
+        Search.jumpToLast();
+        break;
+      case 'page_down':
+        Search.pageDown();
+        break;
+      case 'page_up':
+        Search.pageUp();
+        break;
+      case 'toggle_mark':
+        Search.toggleCurrentMark();
+        break;
+      case 'mark_all':
+        Search.markAll();
+        break;
+      case 'clear_marks':
+        Search.clearMarks();
+        break;
+      case 'delete_note':
+        Search.deleteSelected();
+        break;
+      case 'toggle_secret':
+        Security.toggleSecret(Search.getSelectedNote());
+        break;
     }
   },
 
@@ -218,6 +282,13 @@ const Keyboard = {
   isTyping() {
     const active = document.activeElement;
     return active && (active.tagName === 'INPUT' || active.tagName === 'TEXTAREA');
+  },
+
+  // Keys a text field uses itself: characters and caret movement
+  typesText(e) {
+    if (e.ctrlKey || e.altKey || e.metaKey) return false;
+    return [...e.key].length === 1 ||
+      ['Backspace', 'Delete', 'Home', 'End', 'ArrowLeft', 'ArrowRight'].includes(e.key);
   }
 };
 
@@ -54,6 +54,11 @@ const Search = {
       }
     });
 
+    // Changes from other windows, the HTTP API and background jobs
+    window.__TAURI__.event.listen('store-changed', (event) => {
+      this.handleStoreEvent(event.payload);
+    });
+
     // Initial load
     this.performSearch();
     this.updateTotalCount();
@@ -79,6 +84,41 @@ const Search = {
     }
   },
 
+  // Re-run the current search, keeping the selected note selected
+  async refresh() {
+    const selected = this.getSelectedNote();
+
+    try {
+      this.results = await API.search(this.elements.input.value, 500);
+    } catch (error) {
+      console.error('Refresh failed:', error);
+      return;
+    }
+
+    const index = selected ? this.results.findIndex(r => r.id === selected.id) : -1;
+    this.renderResults();
+    if (this.results.length > 0) {
+      this.selectIndex(Math.max(index, 0));
+    } else {
+      this.selectedIndex = -1;
+      this.showEmptyPreview();
+    }
+  },
+
+  async handleStoreEvent(event) {
+    const deleted = event.kind === 'deleted' ? [event.id]
+      : event.kind === 'bulk_deleted' ? event.ids
+      : [];
+    deleted.forEach(id => this.markedIds.delete(id));
+    if (event.kind === 'reloaded') {
+      this.markedIds.clear();
+    }
+
+    await this.refresh();
+    await this.updateTotalCount();
+    this.updateMarkedStatus();
+  },
+
   renderResults() {
     this.elements.resultsCount.textContent = `(${this.results.length})`;
 
@@ -104,7 +144,7 @@ const Search = {
              data-index="${index}" data-id="${result.id}" tabindex="-1">
           <input type="checkbox" class="result-checkbox" ${isMarked ? 'checked' : ''}>
           <div class="result-content">
-            <div class="result-title">${escapeHtml(displayText)}</div>
+            <div class="result-title">${result.encrypted ? '<span class="result-secret" title="Secret note">&#128274;</span> ' : ''}${escapeHtml(displayText)}</div>
             <div class="result-meta">
               <span class="result-date">${dateStr}</span>
               <span class="result-words">${wordsStr}</span>
@@ -145,6 +185,18 @@ const Search = {
       }
 
       this.elements.previewTitle.textContent = note.title;
+
+      if (note.locked) {
+        this.elements.previewMeta.innerHTML = `
+          Created: ${formatDateTime(note.created_at)} |
+          Updated: ${formatDateTime(note.updated_at)}
+        `;
+        this.elements.previewContent.innerHTML = `
+          <div class="preview-empty">Secret note. Press Enter to unlock.</div>
+        `;
+        return;
+      }
+
       this.elements.previewMeta.innerHTML = `
         Created: ${formatDateTime(note.created_at)} |
         Updated: ${formatDateTime(note.updated_at)} |
@@ -291,12 +343,7 @@ const Search = {
       return;
     }
 
-    try {
-      const result = await API.exportNotes(ids);
-      showToast(result, 'success');
-    } catch (error) {
-      showToast('Export failed: ' + error, 'error');
-    }
+    await Export.open(ids);
   },
 
   async createNote(title) {
@@ -0,0 +1,248 @@
+// Encryption: passphrase dialog, secret notes, auto-lock and the Security settings tab
+
+const Security = {
+  state: { encrypted: false, locked: false },
+  passphraseRequest: null,
+  elements: {},
+
+  async init() {
+    this.elements = {
+      dialog: document.getElementById('unlock-dialog'),
+      title: document.getElementById('unlock-title'),
+      message: document.getElementById('unlock-message'),
+      passphrase: document.getElementById('unlock-passphrase'),
+      error: document.getElementById('unlock-error'),
+      unlockBtn: document.getElementById('unlock-confirm'),
+      cancelBtn: document.getElementById('unlock-cancel'),
+      status: document.getElementById('security-status'),
+      newPassphrase: document.getElementById('security-passphrase'),
+      enableBtn: document.getElementById('security-enable'),
+      disableBtn: document.getElementById('security-disable'),
+      lockBtn: document.getElementById('security-lock'),
+      autoLockInput: document.getElementById('auto-lock-input')
+    };
+
+    this.elements.unlockBtn.addEventListener('click', () => this.submitPassphrase());
+    this.elements.cancelBtn.addEventListener('click', () => this.cancelPassphrase());
+    this.elements.passphrase.addEventListener('keydown', (e) => {
+      if (e.key === 'Enter') {
+        e.preventDefault();
+        e.stopPropagation();
+        this.submitPassphrase();
+      }
+    });
+
+    this.elements.enableBtn.addEventListener('click', () => this.enable());
+    this.elements.disableBtn.addEventListener('click', () => this.disable());
+    this.elements.lockBtn.addEventListener('click', () => this.lockNow());
+    this.elements.autoLockInput.addEventListener('change', () => this.saveAutoLock());
+
+    // The backend locks notes after the auto-lock timeout
+    const { listen } = window.__TAURI__.event;
+    await listen('notes-locked', () => this.handleLocked());
+    await listen('config-changed', (event) => {
+      this.elements.autoLockInput.value = event.payload.security.auto_lock_minutes;
+    });
+
+    try {
+      const settings = await API.getSecuritySettings();
+      this.elements.autoLockInput.value = settings.auto_lock_minutes;
+    } catch (error) {
+      console.error('Failed to load security settings:', error);
+    }
+
+    await this.refresh();
+    if (this.state.locked) {
+      await this.promptUnlock();
+    }
+  },
+
+  async refresh() {
+    try {
+      this.state = await API.getLockState();
+    } catch (error) {
+      console.error('Failed to get lock state:', error);
+    }
+    this.renderStatus();
+  },
+
+  renderStatus() {
+    const { encrypted, locked } = this.state;
+    this.elements.status.textContent = !encrypted
+      ? 'Notes are stored unencrypted.'
+      : locked ? 'Notes are encrypted and locked.' : 'Notes are encrypted.';
+    this.elements.enableBtn.disabled = encrypted;
+    this.elements.disableBtn.disabled = !encrypted;
+    this.elements.lockBtn.disabled = !encrypted || locked;
+  },
+
+  // Ask for a passphrase and pass it to `submit`. Resolves with the result
+  // of `submit` once it succeeds, or null if cancelled.
+  askPassphrase({ title, message, action = 'Unlock', cancellable = true, submit }) {
+    return new Promise((resolve) => {
+      this.passphraseRequest = { submit, resolve };
+      this.elements.title.textContent = title;
+      this.elements.message.textContent = message;
+      this.elements.unlockBtn.textContent = action;
+      this.elements.cancelBtn.style.display = cancellable ? '' : 'none';
+      this.elements.error.textContent = '';
+      this.elements.passphrase.value = '';
+      this.elements.dialog.classList.add('active');
+      this.elements.passphrase.focus();
+    });
+  },
+
+  // Unlock all notes; resolves once the master passphrase is accepted
+  async promptUnlock() {
+    await this.askPassphrase({
+      title: 'Notes Locked',
+      message: 'Enter your passphrase to unlock your notes.',
+      cancellable: false,
+      submit: (passphrase) => API.unlockNotes(passphrase)
+    });
+    await this.refresh();
+  },
+
+  isUnlockOpen() {
+    return this.elements.dialog.classList.contains('active');
+  },
+
+  async submitPassphrase() {
+    const request = this.passphraseRequest;
+    if (!request) return;
+
+    let result;
+    try {
+      result = await request.submit(this.elements.passphrase.value);
+    } catch (error) {
+      this.elements.error.textContent = error;
+      this.elements.passphrase.select();
+      return;
+    }
+
+    this.closePassphrase();
+    request.resolve(result === undefined ? true : result);
+  },
+
+  cancelPassphrase() {
+    const request = this.passphraseRequest;
+    if (!request || this.elements.cancelBtn.style.display === 'none') return;
+
+    this.closePassphrase();
+    request.resolve(null);
+  },
+
+  closePassphrase() {
+    this.passphraseRequest = null;
+    this.elements.passphrase.value = '';
+    this.elements.dialog.classList.remove('active');
+  },
+
+  // Unlock a secret note for this session; resolves with the note or null
+  unlockNote(note) {
+    return this.askPassphrase({
+      title: 'Secret Note',
+      message: `Enter the passphrase for "${note.title}".`,
+      submit: (passphrase) => API.unlockNote(note.id, passphrase)
+    });
+  },
+
+  // Make the selected note secret, or remove its passphrase if it already is
+  async toggleSecret(result) {
+    if (!result) return;
+
+    const done = result.encrypted
+      ? await this.askPassphrase({
+          title: 'Remove Secret',
+          message: `Enter the passphrase for "${result.title}" to store it unencrypted.`,
+          action: 'Remove',
+          submit: (passphrase) => API.clearNoteSecret(result.id, passphrase)
+        })
+      : await this.askPassphrase({
+          title: 'Make Secret',
+          message: `Choose a passphrase for "${result.title}". It cannot be recovered if lost.`,
+          action: 'Encrypt',
+          submit: (passphrase) => API.setNoteSecret(result.id, passphrase)
+        });
+
+    if (done) {
+      showToast(result.encrypted ? 'Note is no longer secret' : 'Note is now secret', 'success');
+      Search.performSearch();
+    }
+  },
+
+  async handleLocked() {
+    await this.refresh();
+
+    // Secret notes were locked too; hide their contents
+    if (!this.state.locked) {
+      Search.performSearch();
+      return;
+    }
+    if (this.isUnlockOpen()) return;
+
+    // An open editor keeps its text and can be saved after unlocking
+    await this.promptUnlock();
+    Search.performSearch();
+  },
+
+  async lockNow() {
+    try {
+      await API.lockNotes();
+      await this.handleLocked();
+    } catch (error) {
+      showToast('Failed to lock notes: ' + error, 'error');
+    }
+  },
+
+  async enable() {
+    const passphrase = this.elements.newPassphrase.value;
+    if (!passphrase) {
+      showToast('Enter a passphrase first', 'warning');
+      return;
+    }
+
+    const confirmed = await Dialogs.confirm(
+      'Encrypt Notes',
+      'Encrypt all notes with this passphrase? <strong>Notes cannot be recovered if the passphrase is lost.</strong>'
+    );
+    if (!confirmed) return;
+
+    try {
+      const message = await API.enableEncryption(passphrase);
+      this.elements.newPassphrase.value = '';
+      await this.refresh();
+      showToast(message, 'success');
+    } catch (error) {
+      showToast('Encryption failed: ' + error, 'error');
+    }
+  },
+
+  async disable() {
+    const passphrase = this.elements.newPassphrase.value;
+    if (!passphrase) {
+      showToast('Enter the current passphrase first', 'warning');
+      return;
+    }
+
+    try {
+      await API.disableEncryption(passphrase);
+      this.elements.newPassphrase.value = '';
+      await this.refresh();
+      showToast('Encryption removed', 'success');
+    } catch (error) {
+      showToast('Failed to remove encryption: ' + error, 'error');
+    }
+  },
+
+  async saveAutoLock() {
+    const minutes = Math.max(0, parseInt(this.elements.autoLockInput.value) || 0);
+    try {
+      await API.saveSecuritySettings({ auto_lock_minutes: minutes });
+    } catch (error) {
+      showToast('Failed to save auto-lock: ' + error, 'error');
+    }
+  }
+};
+
+window.Security = Security;
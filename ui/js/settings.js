@@ -5,6 +5,11 @@ const Settings = {
   currentTab: 'shortcuts',
   settings: {
     preset: 'dark',
+    mode: 'fixed',
+    light_preset: 'light',
+    dark_preset: 'dark',
+    dark_start: '19:00',
+    light_start: '07:00',
     custom_colors: {},
     font_family: 'Inter, system-ui, sans-serif',
     font_size: 16,
@@ -23,6 +28,12 @@ const Settings = {
     'high-contrast': { name: 'High Contrast', bg: '#000000', sidebar: '#0a0a0a', header: '#1a1a1a', accent: '#00ffff' }
   },
 
+  // Themes from ~/.config/kiro/themes, each based on a preset
+  userThemes: [],
+
+  // Preset the backend picked for the "system" and "schedule" modes
+  activePreset: 'dark',
+
   // Customizable colors
   colorVars: [
     { var: '--bg-primary', label: 'Background' },
@@ -37,26 +48,32 @@ const Settings = {
     { var: '--error', label: 'Error' }
   ],
 
-  // Keyboard shortcuts data
+  // Keyboard actions, in the order the Shortcuts tab lists them
   shortcuts: [
-    { keys: ['j', '↓'], desc: 'Navigate down', category: 'Navigation' },
-    { keys: ['k', '↑'], desc: 'Navigate up', category: 'Navigation' },
-    { keys: ['g'], desc: 'Jump to first', category: 'Navigation' },
-    { keys: ['G'], desc: 'Jump to last', category: 'Navigation' },
-    { keys: ['Ctrl+D'], desc: 'Page down', category: 'Navigation' },
-    { keys: ['Ctrl+U'], desc: 'Page up', category: 'Navigation' },
-    { keys: ['Tab'], desc: 'Toggle focus', category: 'Navigation' },
-    { keys: ['Enter'], desc: 'Edit note / Create new', category: 'Actions' },
-    { keys: ['Space'], desc: 'Toggle mark', category: 'Actions' },
-    { keys: ['a'], desc: 'Mark all visible', category: 'Actions' },
-    { keys: ['c'], desc: 'Clear all marks', category: 'Actions' },
-    { keys: ['d'], desc: 'Delete selected', category: 'Actions' },
-    { keys: ['F2'], desc: 'Import notes', category: 'Files' },
-    { keys: ['F3'], desc: 'Export marked', category: 'Files' },
-    { keys: ['Ctrl+S'], desc: 'Save note', category: 'Editor' },
-    { keys: ['Escape'], desc: 'Cancel / Close', category: 'General' },
-    { keys: ['Ctrl+,'], desc: 'Open settings', category: 'General' },
-    { keys: ['?'], desc: 'Show help', category: 'General' }
+    { action: 'move_down', desc: 'Navigate down', category: 'Navigation' },
+    { action: 'move_up', desc: 'Navigate up', category: 'Navigation' },
+    { action: 'jump_first', desc: 'Jump to first', category: 'Navigation' },
+    { action: 'jump_last', desc: 'Jump to last', category: 'Navigation' },
+    { action: 'page_down', desc: 'Page down', category: 'Navigation' },
+    { action: 'page_up', desc: 'Page up', category: 'Navigation' },
+    { action: 'toggle_focus', desc: 'Toggle focus', category: 'Navigation' },
+    { action: 'open_note', desc: 'Edit note / Create new', category: 'Actions' },
+    { action: 'new_from_template', desc: 'New note from template', category: 'Actions' },
+    { action: 'daily_note', desc: "Open today's daily note", category: 'Actions' },
+    { action: 'toggle_mark', desc: 'Toggle mark', category: 'Actions' },
+    { action: 'mark_all', desc: 'Mark all visible', category: 'Actions' },
+    { action: 'clear_marks', desc: 'Clear all marks', category: 'Actions' },
+    { action: 'delete_note', desc: 'Delete selected', category: 'Actions' },
+    { action: 'toggle_secret', desc: 'Make note secret', category: 'Actions' },
+    { action: 'import', desc: 'Import notes', category: 'Files' },
+    { action: 'export_marked', desc: 'Export marked', category: 'Files' },
+    { action: 'save_note', desc: 'Save note', category: 'Editor' },
+    { action: 'previous_day', desc: 'Previous daily note', category: 'Editor' },
+    { action: 'next_day', desc: 'Next daily note', category: 'Editor' },
+    { action: 'cancel', desc: 'Cancel / Close', category: 'General' },
+    { action: 'open_settings', desc: 'Open settings', category: 'General' },
+    { action: 'show_help', desc: 'Show help', category: 'General' },
+    { action: 'quit', desc: 'Quit', category: 'General' }
   ],
 
   elements: {},
@@ -68,8 +85,26 @@ const Settings = {
       closeBtn: document.getElementById('settings-close'),
       tabs: document.querySelectorAll('.settings-tab'),
       themeGrid: document.getElementById('theme-grid'),
+      themeMode: document.getElementById('theme-mode'),
+      themeAuto: document.getElementById('theme-auto'),
+      themeSchedule: document.getElementById('theme-schedule'),
+      lightPresetSelect: document.getElementById('theme-light-preset'),
+      darkPresetSelect: document.getElementById('theme-dark-preset'),
+      darkStartInput: document.getElementById('theme-dark-start'),
+      lightStartInput: document.getElementById('theme-light-start'),
+      themeName: document.getElementById('theme-name'),
+      themeCreateBtn: document.getElementById('theme-create'),
+      themePath: document.getElementById('theme-path'),
+      themeImportBtn: document.getElementById('theme-import'),
+      themeExportBtn: document.getElementById('theme-export'),
+      themeDeleteBtn: document.getElementById('theme-delete'),
       colorGrid: document.getElementById('color-grid'),
       shortcutsList: document.getElementById('shortcuts-list'),
+      profileSelect: document.getElementById('keymap-profile-select'),
+      profileLoadBtn: document.getElementById('keymap-profile-load'),
+      profileName: document.getElementById('keymap-profile-name'),
+      profileSaveBtn: document.getElementById('keymap-profile-save'),
+      keymapResetBtn: document.getElementById('keymap-reset'),
       fontFamilySelect: document.getElementById('font-family-select'),
       fontSizeInput: document.getElementById('font-size-input'),
       monoFontSelect: document.getElementById('mono-font-select'),
@@ -79,7 +114,9 @@ const Settings = {
     };
 
     // Load saved settings
+    await this.loadUserThemes();
     await this.loadSettings();
+    await this.loadActiveTheme();
 
     // Set up event listeners
     this.setupEventListeners();
@@ -88,9 +125,22 @@ const Settings = {
     this.renderThemeGrid();
     this.renderColorGrid();
     this.renderShortcuts();
+    this.loadKeymapProfiles();
 
     // Apply current settings
     this.applySettings();
+
+    // config.json edited outside the app
+    window.__TAURI__.event.listen('config-changed', (event) => {
+      this.handleConfigChanged(event.payload);
+    });
+
+    // The desktop's color scheme changed or the schedule reached a start time
+    window.__TAURI__.event.listen('theme-changed', (event) => {
+      this.activePreset = event.payload;
+      this.applySettings();
+      this.renderColorGrid();
+    });
   },
 
   setupEventListeners() {
@@ -107,11 +157,41 @@ const Settings = {
       tab.addEventListener('click', () => this.switchTab(tab.dataset.tab));
     });
 
+    // Automatic light/dark theme
+    this.elements.themeMode.addEventListener('change', () => {
+      this.settings.mode = this.elements.themeMode.value;
+      this.updateModeFields();
+    });
+    this.elements.lightPresetSelect.addEventListener('change', () => {
+      this.settings.light_preset = this.elements.lightPresetSelect.value;
+    });
+    this.elements.darkPresetSelect.addEventListener('change', () => {
+      this.settings.dark_preset = this.elements.darkPresetSelect.value;
+    });
+    this.elements.darkStartInput.addEventListener('change', () => {
+      this.settings.dark_start = this.elements.darkStartInput.value;
+    });
+    this.elements.lightStartInput.addEventListener('change', () => {
+      this.settings.light_start = this.elements.lightStartInput.value;
+    });
+
+    // Theme files
+    this.elements.themeCreateBtn.addEventListener('click', () => this.createTheme());
+    this.elements.themeImportBtn.addEventListener('click', () => this.importTheme());
+    this.elements.themeExportBtn.addEventListener('click', () => this.exportTheme());
+    this.elements.themeDeleteBtn.addEventListener('click', () => this.deleteTheme());
+
     // Font settings
     this.elements.fontFamilySelect.addEventListener('change', () => this.updateFontPreview());
     this.elements.fontSizeInput.addEventListener('input', () => this.updateFontPreview());
     this.elements.monoFontSelect.addEventListener('change', () => this.updateFontPreview());
 
+    // Shortcuts: add, remove or reset chords, and keymap profiles
+    this.elements.shortcutsList.addEventListener('click', (e) => this.handleShortcutClick(e));
+    this.elements.profileLoadBtn.addEventListener('click', () => this.importKeymapProfile());
+    this.elements.profileSaveBtn.addEventListener('click', () => this.exportKeymapProfile());
+    this.elements.keymapResetBtn.addEventListener('click', () => this.resetKeybindings(null));
+
     // Save and Reset
     this.elements.saveBtn.addEventListener('click', () => this.saveSettings());
     this.elements.resetBtn.addEventListener('click', () => this.resetSettings());
@@ -119,30 +199,95 @@ const Settings = {
 
   async loadSettings() {
     try {
-      const settings = await API.getThemeSettings();
-      this.settings = {
-        preset: settings.preset || 'dark',
-        custom_colors: settings.custom_colors || {},
-        font_family: settings.font_family || 'Inter, system-ui, sans-serif',
-        font_size: settings.font_size || 16,
-        mono_font: settings.mono_font || "'JetBrains Mono', monospace"
-      };
-
-      // Update form values
-      this.elements.fontFamilySelect.value = this.settings.font_family;
-      this.elements.fontSizeInput.value = this.settings.font_size;
-      this.elements.monoFontSelect.value = this.settings.mono_font;
+      this.setThemeSettings(await API.getThemeSettings());
     } catch (error) {
       console.error('Failed to load settings:', error);
     }
   },
 
-  applySettings() {
-    // Apply theme
-    document.documentElement.setAttribute('data-theme', this.settings.preset);
+  async loadActiveTheme() {
+    try {
+      this.activePreset = await API.getActiveTheme();
+    } catch (error) {
+      console.error('Failed to load active theme:', error);
+    }
+  },
+
+  async loadUserThemes() {
+    try {
+      this.userThemes = await API.listThemes();
+    } catch (error) {
+      console.error('Failed to load themes:', error);
+    }
+  },
+
+  userTheme(id) {
+    return this.userThemes.find(theme => theme.id === id);
+  },
+
+  setThemeSettings(settings) {
+    this.settings = {
+      preset: settings.preset || 'dark',
+      mode: settings.mode || 'fixed',
+      light_preset: settings.light_preset || 'light',
+      dark_preset: settings.dark_preset || 'dark',
+      dark_start: settings.dark_start || '19:00',
+      light_start: settings.light_start || '07:00',
+      custom_colors: settings.custom_colors || {},
+      font_family: settings.font_family || 'Inter, system-ui, sans-serif',
+      font_size: settings.font_size || 16,
+      mono_font: settings.mono_font || "'JetBrains Mono', monospace"
+    };
+
+    // Update form values
+    this.elements.fontFamilySelect.value = this.settings.font_family;
+    this.elements.fontSizeInput.value = this.settings.font_size;
+    this.elements.monoFontSelect.value = this.settings.mono_font;
+    this.updateModeFields();
+  },
+
+  // Mode form: the light/dark presets only matter outside the "fixed"
+  // mode, the start times only for "schedule"
+  updateModeFields() {
+    const mode = this.settings.mode;
+    this.elements.themeMode.value = mode;
+    this.elements.themeAuto.style.display = mode === 'fixed' ? 'none' : '';
+    this.elements.themeSchedule.style.display = mode === 'schedule' ? '' : 'none';
+    this.elements.darkStartInput.value = this.settings.dark_start;
+    this.elements.lightStartInput.value = this.settings.light_start;
+
+    const options = this.allThemes()
+      .map(([id, theme]) => `<option value="${id}">${theme.name}</option>`)
+      .join('');
+    this.elements.lightPresetSelect.innerHTML = options;
+    this.elements.darkPresetSelect.innerHTML = options;
+    this.elements.lightPresetSelect.value = this.settings.light_preset;
+    this.elements.darkPresetSelect.value = this.settings.dark_preset;
+  },
+
+  // The preset on screen: the selected one, or the one the backend picked
+  // for the desktop's color scheme or the time of day
+  shownPreset() {
+    return this.settings.mode === 'fixed' ? this.settings.preset : this.activePreset;
+  },
 
-    // Apply custom colors
-    for (const [varName, value] of Object.entries(this.settings.custom_colors)) {
+  handleConfigChanged(config) {
+    this.setThemeSettings(config.theme);
+    this.applySettings();
+    this.renderThemeGrid();
+    this.renderColorGrid();
+  },
+
+  applySettings() {
+    // Apply theme; a user theme sets its colors over its base preset
+    const preset = this.shownPreset();
+    const userTheme = this.userTheme(preset);
+    document.documentElement.setAttribute('data-theme', userTheme ? userTheme.base : preset);
+
+    // Colors from an earlier theme or removed overrides must not linger
+    this.clearColorOverrides();
+    const colors = { ...(userTheme ? userTheme.colors : {}), ...this.settings.custom_colors };
+    for (const [varName, value] of Object.entries(colors)) {
       document.documentElement.style.setProperty(varName, value);
     }
 
@@ -172,6 +317,11 @@ const Settings = {
   async resetSettings() {
     this.settings = {
       preset: 'dark',
+      mode: 'fixed',
+      light_preset: 'light',
+      dark_preset: 'dark',
+      dark_start: '19:00',
+      light_start: '07:00',
       custom_colors: {},
       font_family: 'Inter, system-ui, sans-serif',
       font_size: 16,
@@ -182,11 +332,7 @@ const Settings = {
     this.elements.fontFamilySelect.value = this.settings.font_family;
     this.elements.fontSizeInput.value = this.settings.font_size;
     this.elements.monoFontSelect.value = this.settings.mono_font;
-
-    // Clear custom colors from CSS
-    this.colorVars.forEach(cv => {
-      document.documentElement.style.removeProperty(cv.var);
-    });
+    this.updateModeFields();
 
     this.applySettings();
     this.renderThemeGrid();
@@ -232,8 +378,31 @@ const Settings = {
     });
   },
 
+  // Remove inline color variables so the preset's own colors show through
+  clearColorOverrides() {
+    const style = document.documentElement.style;
+    [...style]
+      .filter(name => name.startsWith('--') && !name.startsWith('--font'))
+      .forEach(name => style.removeProperty(name));
+  },
+
+  // Built-in presets followed by user themes, with preview colors
+  allThemes() {
+    const userThemes = this.userThemes.map(theme => {
+      const base = this.themes[theme.base] || this.themes.dark;
+      return [theme.id, {
+        name: escapeHtml(theme.name),
+        bg: theme.colors['--bg-primary'] || base.bg,
+        sidebar: theme.colors['--bg-secondary'] || base.sidebar,
+        header: theme.colors['--bg-tertiary'] || base.header,
+        accent: theme.colors['--accent'] || base.accent
+      }];
+    });
+    return [...Object.entries(this.themes), ...userThemes];
+  },
+
   renderThemeGrid() {
-    this.elements.themeGrid.innerHTML = Object.entries(this.themes).map(([id, theme]) => `
+    this.elements.themeGrid.innerHTML = this.allThemes().map(([id, theme]) => `
       <div class="theme-option ${this.settings.preset === id ? 'selected' : ''}" data-theme="${id}">
         <div class="theme-preview">
           <div class="theme-preview-sidebar" style="background: ${theme.sidebar}"></div>
@@ -256,23 +425,110 @@ const Settings = {
     this.settings.preset = themeId;
     this.settings.custom_colors = {}; // Clear custom colors when changing theme
 
+    // Picking a theme turns off automatic switching
+    this.settings.mode = 'fixed';
+    this.updateModeFields();
+
     // Update selection UI
     this.elements.themeGrid.querySelectorAll('.theme-option').forEach(option => {
       option.classList.toggle('selected', option.dataset.theme === themeId);
     });
 
     // Apply theme immediately
-    document.documentElement.setAttribute('data-theme', themeId);
-
-    // Clear custom color overrides
-    this.colorVars.forEach(cv => {
-      document.documentElement.style.removeProperty(cv.var);
-    });
+    this.applySettings();
 
     // Re-render color grid with new theme colors
     this.renderColorGrid();
   },
 
+  // The selected theme if it is a user theme, warning otherwise
+  selectedUserTheme() {
+    const theme = this.userTheme(this.settings.preset);
+    if (!theme) {
+      showToast('Select one of your own themes first', 'warning');
+    }
+    return theme;
+  },
+
+  // Save the current preset and custom colors as a new user theme
+  async createTheme() {
+    const name = this.elements.themeName.value.trim();
+    if (!name) {
+      showToast('Enter a theme name', 'warning');
+      return;
+    }
+
+    try {
+      const theme = await API.createTheme(name);
+      this.elements.themeName.value = '';
+      await this.loadUserThemes();
+      this.settings.preset = theme.id;
+      this.settings.mode = 'fixed';
+      this.settings.custom_colors = {};
+      this.updateModeFields();
+      this.applySettings();
+      this.renderThemeGrid();
+      this.renderColorGrid();
+      showToast(`Saved theme "${escapeHtml(theme.name)}"`, 'success');
+    } catch (error) {
+      showToast('Failed to save theme: ' + error, 'error');
+    }
+  },
+
+  async importTheme() {
+    const path = this.elements.themePath.value.trim();
+    if (!path) {
+      showToast('Enter the path of a theme file', 'warning');
+      return;
+    }
+
+    try {
+      const theme = await API.importTheme(path);
+      this.elements.themePath.value = '';
+      await this.loadUserThemes();
+      this.renderThemeGrid();
+      this.updateModeFields();
+      showToast(`Imported theme "${escapeHtml(theme.name)}"`, 'success');
+    } catch (error) {
+      showToast('Failed to import theme: ' + error, 'error');
+    }
+  },
+
+  async exportTheme() {
+    const theme = this.selectedUserTheme();
+    const path = this.elements.themePath.value.trim();
+    if (!theme) return;
+    if (!path) {
+      showToast('Enter a path to export to', 'warning');
+      return;
+    }
+
+    try {
+      showToast(await API.exportTheme(theme.id, path), 'success');
+    } catch (error) {
+      showToast('Failed to export theme: ' + error, 'error');
+    }
+  },
+
+  async deleteTheme() {
+    const theme = this.selectedUserTheme();
+    if (!theme) return;
+
+    const confirmed = await Dialogs.confirm('Delete Theme', `Delete the theme "${escapeHtml(theme.name)}"?`);
+    if (!confirmed) return;
+
+    try {
+      await API.deleteTheme(theme.id);
+      await this.loadUserThemes();
+      await this.loadSettings();
+      this.applySettings();
+      this.renderThemeGrid();
+      this.renderColorGrid();
+    } catch (error) {
+      showToast('Failed to delete theme: ' + error, 'error');
+    }
+  },
+
   renderColorGrid() {
     this.elements.colorGrid.innerHTML = this.colorVars.map(cv => {
       const currentValue = this.settings.custom_colors[cv.var] ||
@@ -323,29 +579,114 @@ const Settings = {
   },
 
   renderShortcuts() {
-    // Group shortcuts by category
-    const grouped = {};
-    this.shortcuts.forEach(s => {
-      if (!grouped[s.category]) grouped[s.category] = [];
-      grouped[s.category].push(s);
-    });
+    // Keyboard sets the keymap before or after this panel is ready
+    if (!this.elements.shortcutsList) return;
 
-    let html = '';
-    for (const [category, items] of Object.entries(grouped)) {
-      items.forEach(item => {
-        html += `
-          <div class="shortcut-row">
-            <div class="shortcut-keys">
-              ${item.keys.map(k => `<span class="shortcut-key">${k}</span>`).join('')}
-            </div>
-            <span class="shortcut-desc">${item.desc}</span>
-            <span class="shortcut-category">${category}</span>
+    this.elements.shortcutsList.innerHTML = this.shortcuts.map(item => {
+      const chords = Keyboard.keymap[item.action] || [];
+      const keys = chords.map((chord, index) => `
+        <span class="shortcut-key">${escapeHtml(Keyboard.formatChord(chord))}<button class="shortcut-remove" data-index="${index}" title="Remove">&times;</button></span>
+      `).join('');
+
+      return `
+        <div class="shortcut-row" data-action="${item.action}">
+          <div class="shortcut-keys">
+            ${keys}
+            <button class="shortcut-add" title="Add a shortcut">+</button>
           </div>
-        `;
+          <span class="shortcut-desc">${item.desc}</span>
+          <span class="shortcut-category">${item.category}</span>
+          <button class="shortcut-reset" title="Reset to default">&#8634;</button>
+        </div>
+      `;
+    }).join('');
+  },
+
+  handleShortcutClick(e) {
+    const button = e.target.closest('button');
+    const row = e.target.closest('.shortcut-row');
+    if (!button || !row) return;
+
+    const action = row.dataset.action;
+    const chords = Keyboard.keymap[action] || [];
+
+    if (button.classList.contains('shortcut-add')) {
+      button.textContent = 'Press keys…';
+      Keyboard.captureChord((chord) => {
+        if (chord) {
+          this.setKeybinding(action, [...chords, chord]);
+        } else {
+          this.renderShortcuts();
+        }
       });
+    } else if (button.classList.contains('shortcut-remove')) {
+      const index = Number(button.dataset.index);
+      this.setKeybinding(action, chords.filter((_, i) => i !== index));
+    } else if (button.classList.contains('shortcut-reset')) {
+      this.resetKeybindings(action);
+    }
+  },
+
+  async setKeybinding(action, chords) {
+    try {
+      Keyboard.setKeymap(await API.setKeybinding(action, chords));
+    } catch (error) {
+      showToast('Failed to change shortcut: ' + error, 'error');
+      this.renderShortcuts();
+    }
+  },
+
+  // Reset one action, or all of them when `action` is null
+  async resetKeybindings(action) {
+    try {
+      Keyboard.setKeymap(await API.resetKeybindings(action));
+    } catch (error) {
+      showToast('Failed to reset shortcuts: ' + error, 'error');
     }
+  },
 
-    this.elements.shortcutsList.innerHTML = html;
+  async loadKeymapProfiles() {
+    try {
+      const profiles = await API.listKeymapProfiles();
+      this.elements.profileSelect.innerHTML = profiles.length > 0
+        ? profiles.map(name => `<option value="${escapeHtml(name)}">${escapeHtml(name)}</option>`).join('')
+        : '<option value="">No saved profiles</option>';
+      this.elements.profileLoadBtn.disabled = profiles.length === 0;
+    } catch (error) {
+      console.error('Failed to list keymap profiles:', error);
+    }
+  },
+
+  async importKeymapProfile() {
+    const name = this.elements.profileSelect.value;
+    if (!name) return;
+
+    try {
+      const profile = await API.importKeymapProfile(name);
+      Keyboard.setKeymap(profile.keymap);
+      showToast(`Loaded keymap "${escapeHtml(name)}"`, 'success');
+      profile.warnings.forEach(warning => showToast(escapeHtml(warning), 'warning', 10000));
+    } catch (error) {
+      showToast('Failed to load keymap: ' + error, 'error');
+    }
+  },
+
+  async exportKeymapProfile() {
+    const name = this.elements.profileName.value.trim();
+    if (!name) {
+      showToast('Enter a profile name', 'warning');
+      return;
+    }
+
+    try {
+      await API.exportKeymapProfile(name);
+      this.elements.profileName.value = '';
+      await this.loadKeymapProfiles();
+      this.elements.profileSelect.value = name;
+      showToast(`Saved keymap "${escapeHtml(name)}"`, 'success');
+    } catch (error) {
+      showToast('Failed to save keymap: ' + error, 'error');
+    }
   },
 
   updateFontPreview() {
@@ -0,0 +1,165 @@
+// Note templates: the new-from-template dialog and the Templates settings tab
+
+const Templates = {
+  templates: [],
+  elements: {},
+
+  async init() {
+    this.elements = {
+      dialog: document.getElementById('template-dialog'),
+      select: document.getElementById('template-select'),
+      prompts: document.getElementById('template-prompts'),
+      createBtn: document.getElementById('template-create'),
+      cancelBtn: document.getElementById('template-cancel'),
+      closeBtn: document.getElementById('template-close'),
+      editSelect: document.getElementById('template-edit-select'),
+      newBtn: document.getElementById('template-new'),
+      deleteBtn: document.getElementById('template-delete'),
+      name: document.getElementById('template-name'),
+      source: document.getElementById('template-source'),
+      saveBtn: document.getElementById('template-save')
+    };
+
+    this.elements.select.addEventListener('change', () => this.renderPrompts());
+    this.elements.createBtn.addEventListener('click', () => this.create());
+    this.elements.cancelBtn.addEventListener('click', () => this.close());
+    this.elements.closeBtn.addEventListener('click', () => this.close());
+    this.elements.dialog.addEventListener('click', (e) => {
+      if (e.target === this.elements.dialog) this.close();
+    });
+
+    this.elements.editSelect.addEventListener('change', () => this.edit(this.elements.editSelect.value));
+    this.elements.newBtn.addEventListener('click', () => this.edit(null));
+    this.elements.deleteBtn.addEventListener('click', () => this.remove());
+    this.elements.saveBtn.addEventListener('click', () => this.save());
+
+    await this.refresh();
+  },
+
+  async refresh() {
+    try {
+      this.templates = await API.listNoteTemplates();
+    } catch (error) {
+      console.error('Failed to list note templates:', error);
+    }
+
+    const options = this.templates
+      .map(template => `<option value="${escapeHtml(template.name)}">${escapeHtml(template.name)}</option>`)
+      .join('');
+    this.elements.select.innerHTML = options;
+    this.elements.editSelect.innerHTML = `<option value="">New template…</option>${options}`;
+    Journal.renderTemplateOptions();
+  },
+
+  isOpen() {
+    return this.elements.dialog.classList.contains('active');
+  },
+
+  // Pick a template and answer its prompts; the search text fills `title`
+  async open() {
+    await this.refresh();
+    if (this.templates.length === 0) {
+      showToast('No note templates yet; add one in Settings > Templates', 'warning');
+      return;
+    }
+
+    this.renderPrompts();
+    this.elements.dialog.classList.add('active');
+    this.elements.select.focus();
+  },
+
+  close() {
+    this.elements.dialog.classList.remove('active');
+    document.getElementById('search-input').focus();
+  },
+
+  renderPrompts() {
+    const template = this.templates.find(t => t.name === this.elements.select.value);
+    const title = document.getElementById('search-input').value.trim();
+    this.elements.prompts.innerHTML = (template ? template.prompts : []).map(name => `
+      <div class="font-setting">
+        <label class="font-label">${escapeHtml(name)}</label>
+        <input type="text" class="font-select" data-var="${escapeHtml(name)}"
+          value="${name === 'title' ? escapeHtml(title) : ''}">
+      </div>
+    `).join('');
+  },
+
+  async create() {
+    const template = this.elements.select.value;
+    const vars = {};
+    this.elements.prompts.querySelectorAll('input[data-var]').forEach(input => {
+      vars[input.dataset.var] = input.value;
+    });
+
+    try {
+      const note = await API.createNoteFromTemplate(template, vars);
+      this.elements.dialog.classList.remove('active');
+      await Search.performSearch();
+      await Search.updateTotalCount();
+
+      const index = Search.results.findIndex(r => r.id === note.id);
+      if (index >= 0) {
+        Search.selectIndex(index);
+      }
+      Editor.editNote({ id: note.id }, note.cursor);
+    } catch (error) {
+      showToast('Failed to create note: ' + error, 'error');
+    }
+  },
+
+  // Load a template into the Templates tab, or clear it for a new one
+  async edit(name) {
+    this.elements.editSelect.value = name || '';
+    this.elements.name.value = name || '';
+    this.elements.source.value = '';
+    if (!name) {
+      this.elements.name.focus();
+      return;
+    }
+
+    try {
+      this.elements.source.value = await API.getNoteTemplate(name);
+    } catch (error) {
+      showToast('Failed to load template: ' + error, 'error');
+    }
+  },
+
+  async save() {
+    const name = this.elements.name.value.trim();
+    if (!name) {
+      showToast('Enter a template name', 'warning');
+      return;
+    }
+
+    try {
+      await API.saveNoteTemplate(name, this.elements.source.value);
+      await this.refresh();
+      this.elements.editSelect.value = name;
+      showToast(`Saved template "${escapeHtml(name)}"`, 'success');
+    } catch (error) {
+      showToast('Failed to save template: ' + error, 'error');
+    }
+  },
+
+  async remove() {
+    const name = this.elements.editSelect.value;
+    if (!name) {
+      showToast('Select a template first', 'warning');
+      return;
+    }
+
+    const confirmed = await Dialogs.confirm('Delete Template', `Delete the template "${escapeHtml(name)}"?`);
+    if (!confirmed) return;
+
+    try {
+      await API.deleteNoteTemplate(name);
+      await this.refresh();
+      await this.edit(null);
+    } catch (error) {
+      showToast('Failed to delete template: ' + error, 'error');
+    }
+  }
+};
+
+window.Templates = Templates;
@@ -0,0 +1,135 @@
+// Vaults: the Vaults settings tab and switching between notes databases
+
+const Vaults = {
+  vaults: [],
+  elements: {},
+
+  async init() {
+    this.elements = {
+      list: document.getElementById('vault-list'),
+      name: document.getElementById('vault-name'),
+      path: document.getElementById('vault-path'),
+      createBtn: document.getElementById('vault-create'),
+      openBtn: document.getElementById('vault-open'),
+      status: document.getElementById('status-vault')
+    };
+
+    this.elements.createBtn.addEventListener('click', () => this.add(false));
+    this.elements.openBtn.addEventListener('click', () => this.add(true));
+    this.elements.list.addEventListener('click', (e) => {
+      const button = e.target.closest('button[data-action]');
+      if (!button) return;
+      const vault = this.vaults[button.dataset.index];
+      if (button.dataset.action === 'switch') {
+        this.switchTo(vault.name);
+      } else {
+        this.close(vault);
+      }
+    });
+
+    const { listen } = window.__TAURI__.event;
+    await listen('vault-changed', (event) => this.handleVaultChanged(event.payload));
+
+    await this.refresh();
+  },
+
+  async refresh() {
+    try {
+      this.vaults = await API.listVaults();
+    } catch (error) {
+      console.error('Failed to list vaults:', error);
+    }
+    this.render();
+  },
+
+  render() {
+    this.elements.list.innerHTML = this.vaults.map((vault, index) => `
+      <div class="vault-item${vault.current ? ' current' : ''}">
+        <div class="vault-info">
+          <span class="vault-name">${escapeHtml(vault.name)}</span>
+          <span class="vault-path">${escapeHtml(vault.path)}${vault.exists ? '' : ' (not created yet)'}</span>
+        </div>
+        <button class="btn btn-secondary" data-action="switch" data-index="${index}" ${vault.current ? 'disabled' : ''}>
+          ${vault.current ? 'Open' : 'Switch'}
+        </button>
+        ${index === 0 ? '' : `<button class="btn btn-secondary" data-action="close" data-index="${index}">Close</button>`}
+      </div>
+    `).join('');
+
+    // The status bar names the vault unless it's the default one
+    const current = this.vaults.find(vault => vault.current);
+    this.elements.status.textContent = current && current !== this.vaults[0] ? `Vault: ${current.name}` : '';
+  },
+
+  // Add a vault from the form, creating a new database or opening an
+  // existing one, and switch to it
+  async add(existing) {
+    const name = this.elements.name.value.trim();
+    const path = this.elements.path.value.trim();
+    if (!name || !path) {
+      showToast('Enter a name and a database path', 'warning');
+      return;
+    }
+
+    try {
+      const vault = existing ? await API.openVault(name, path) : await API.createVault(name, path);
+      this.elements.name.value = '';
+      this.elements.path.value = '';
+      await this.switchTo(vault.name);
+    } catch (error) {
+      showToast('Failed to add vault: ' + error, 'error');
+    }
+  },
+
+  async switchTo(name) {
+    if (Editor.isActive && Editor.isDirty) {
+      const confirmed = await Dialogs.confirm(
+        'Unsaved Changes',
+        'Switching vaults discards your unsaved changes. Continue?'
+      );
+      if (!confirmed) return;
+    }
+
+    try {
+      await API.switchVault(name);
+    } catch (error) {
+      showToast('Failed to switch vault: ' + error, 'error');
+    }
+  },
+
+  async close(vault) {
+    const confirmed = await Dialogs.confirm(
+      'Close Vault',
+      `Remove "${escapeHtml(vault.name)}" from the vault list? Its database stays at ${escapeHtml(vault.path)}.`
+    );
+    if (!confirmed) return;
+
+    try {
+      await API.closeVault(vault.name);
+      await this.refresh();
+    } catch (error) {
+      showToast('Failed to close vault: ' + error, 'error');
+    }
+  },
+
+  // Another database is open: start over with its notes and lock state
+  async handleVaultChanged(vault) {
+    if (Editor.isActive) {
+      Editor.close();
+    }
+    Search.markedIds.clear();
+
+    await this.refresh();
+    await Security.refresh();
+    if (Security.state.locked && !Security.isUnlockOpen()) {
+      await Security.promptUnlock();
+    }
+
+    await Search.performSearch();
+    await Search.updateTotalCount();
+    Search.updateMarkedStatus();
+    showToast(`Opened vault "${escapeHtml(vault.name)}"`, 'success');
+  }
+};
+
+window.Vaults = Vaults;